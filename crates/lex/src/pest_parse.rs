@@ -12,21 +12,19 @@ pub struct SlParser;
 
 pub fn sl_parse_file(filename: &str) -> TokenIterator {
     let unparsed_file = fs::read_to_string(filename).expect("cannot read file");
-    // filter comments away
-    let source_string = unparsed_file
-        .lines()
-        .filter(|line| !line.trim_start().starts_with('#'))
-        .collect::<Vec<&str>>()
-        .join("\n");
-    let tokens = SlParser::parse(Rule::source, &source_string);
-    let tokens = match tokens {
-        Ok(mut tokens) => tokens.next().unwrap(),
+    match sl_parse_str(&unparsed_file) {
+        Ok(tokens) => tokens,
         Err(e) => {
             error!("[compile error] Invalid syntax:\n {}", e);
             std::process::exit(0);
         }
-    };
-    // dbg!(&tokens);
+    }
+}
 
-    tokens.into_token_iter()
+/// Tokenize source text, the error is pest's syntax error message
+pub fn sl_parse_str(source: &str) -> Result<TokenIterator, String> {
+    match SlParser::parse(Rule::source, source) {
+        Ok(mut tokens) => Ok(tokens.next().unwrap().into_token_iter()),
+        Err(e) => Err(e.to_string()),
+    }
 }
@@ -1,4 +1,7 @@
-use crate::pest_parse::{sl_parse_file, Rule, SlParser};
+use crate::{
+    pest_parse::{sl_parse_file, sl_parse_str, Rule, SlParser},
+    token::Token,
+};
 use pest::Parser;
 use std::fs;
 
@@ -25,3 +28,25 @@ fn test_lex_iter() {
         println!("{:?}", token);
     }
 }
+
+#[test]
+fn test_lex_comments() {
+    // lines starting with `#` are comments, also on the first line and after blanks
+    let source = "  #first\n#word\nlet n = #s;\n\t# tabbed\nx <// a { //> # y";
+    let tokens: Vec<Token> = sl_parse_str(source).unwrap().collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::Let,
+            Token::Identifier("n".into()),
+            Token::Assign,
+            Token::Hash,
+            Token::Identifier("s".into()),
+            Token::Semicolon,
+            Token::Identifier("x".into()),
+            Token::Hash,
+            Token::Identifier("y".into()),
+            Token::Eof,
+        ]
+    );
+}
@@ -27,26 +27,38 @@ pub enum Token {
     Int(i64),
     Float(f64),
     String(String),
+    // r"...", not interpolated
+    RawString(String),
     Bool(bool),
     Teer(teer),
     Nil,
 
     // symbols
     Neq,
+    Cmp,
     And,
+    Amp,
+    Shl,
     Leq,
     Eq,
+    FatArrow,
     Geq,
+    Shr,
     Or,
+    Pipe,
+    Caret,
+    Tilde,
     Not,
     Hash,
     Percent,
     Lpar,
     Rpar,
+    Pow,
     Star,
     Plus,
     Comma,
     Minus,
+    Ellipsis,
     Dot,
     Slash,
     Colon,
@@ -62,6 +74,7 @@ pub enum Token {
 
     // reserved
     Let,
+    Const,
     Func,
     Typeof,
     If,
@@ -72,6 +85,12 @@ pub enum Token {
     While,
     Return,
     New,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Match,
+    Defer,
 
     //
     Identifier(String),
@@ -38,27 +38,42 @@ impl IntoTokenIterator for pest::iterators::Pair<'_, Rule> {
                     // literals
                     Rule::int => Token::Int(str_repr.parse().unwrap()),
                     Rule::float => Token::Float(str_repr.parse().unwrap()),
-                    Rule::string => Token::String(str_repr.to_string()),
+                    Rule::string_wrapper => {
+                        Token::String(pr.into_inner().next().unwrap().as_str().to_string())
+                    }
+                    Rule::raw_string_wrapper => {
+                        Token::RawString(pr.into_inner().next().unwrap().as_str().to_string())
+                    }
                     Rule::bool => Token::Bool(str_repr.parse().unwrap()),
                     Rule::teer => Token::Teer(str_repr.parse().unwrap()),
                     Rule::nil => Token::Nil,
 
                     // signs
                     Rule::neq => Token::Neq,
+                    Rule::cmp => Token::Cmp,
                     Rule::and => Token::And,
+                    Rule::amp => Token::Amp,
+                    Rule::shl => Token::Shl,
                     Rule::leq => Token::Leq,
                     Rule::eq => Token::Eq,
+                    Rule::fat_arrow => Token::FatArrow,
                     Rule::geq => Token::Geq,
+                    Rule::shr => Token::Shr,
                     Rule::or => Token::Or,
+                    Rule::pipe => Token::Pipe,
+                    Rule::caret => Token::Caret,
+                    Rule::tilde => Token::Tilde,
                     Rule::not => Token::Not,
                     Rule::hash => Token::Hash,
                     Rule::percent => Token::Percent,
                     Rule::lpar => Token::Lpar,
                     Rule::rpar => Token::Rpar,
+                    Rule::pow => Token::Pow,
                     Rule::star => Token::Star,
                     Rule::plus => Token::Plus,
                     Rule::comma => Token::Comma,
                     Rule::minus => Token::Minus,
+                    Rule::ellipsis => Token::Ellipsis,
                     Rule::dot => Token::Dot,
                     Rule::slash => Token::Slash,
                     Rule::colon => Token::Colon,
@@ -74,6 +89,7 @@ impl IntoTokenIterator for pest::iterators::Pair<'_, Rule> {
 
                     // reserved
                     Rule::r_let => Token::Let,
+                    Rule::r_const => Token::Const,
                     Rule::r_func => Token::Func,
                     Rule::r_typeof => Token::Typeof,
                     Rule::r_if => Token::If,
@@ -84,6 +100,12 @@ impl IntoTokenIterator for pest::iterators::Pair<'_, Rule> {
                     Rule::r_while => Token::While,
                     Rule::r_return => Token::Return,
                     Rule::r_new => Token::New,
+                    Rule::r_throw => Token::Throw,
+                    Rule::r_try => Token::Try,
+                    Rule::r_catch => Token::Catch,
+                    Rule::r_finally => Token::Finally,
+                    Rule::r_match => Token::Match,
+                    Rule::r_defer => Token::Defer,
 
                     // identifier
                     Rule::ident => Token::Identifier(str_repr.to_string()),
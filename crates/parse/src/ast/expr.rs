@@ -18,6 +18,14 @@ pub enum ExprNode {
     },
 
     // non atomic
+    And {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    Or {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
     Eq {
         left: Box<ExprNode>,
         right: Box<ExprNode>,
@@ -43,6 +51,30 @@ pub enum ExprNode {
         left: Box<ExprNode>,
         right: Box<ExprNode>,
     },
+    Cmp {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    BitOr {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    BitXor {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    BitAnd {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    Shl {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
+    Shr {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
     Add {
         left: Box<ExprNode>,
         right: Box<ExprNode>,
@@ -63,15 +95,44 @@ pub enum ExprNode {
         left: Box<ExprNode>,
         right: Box<ExprNode>,
     },
+    // right associative: a ** b ** c is a ** (b ** c)
+    Pow {
+        left: Box<ExprNode>,
+        right: Box<ExprNode>,
+    },
     Not(Box<ExprNode>),
     Neg(Box<ExprNode>),
+    // ~x
+    BitNot(Box<ExprNode>),
+    // #x
+    Len(Box<ExprNode>),
+
+    // x[i]
+    Index {
+        base: Box<ExprNode>,
+        index: Box<ExprNode>,
+    },
+    // x[a:b], either bound may be omitted
+    Slice {
+        base: Box<ExprNode>,
+        start: Option<Box<ExprNode>>,
+        end: Option<Box<ExprNode>>,
+    },
 
     // pathed with Lpar and Rpar
     Packed(Box<ExprNode>),
+    // (a, b, ...)
+    Tuple(Vec<ExprNode>),
+    // [a, b, ...]
+    List(Vec<ExprNode>),
+    // "text {expr} text": string literals and expressions, concatenated as text
+    Interpolated(Vec<ExprNode>),
 
     Call {
         name: String,
         args: Vec<ExprNode>,
+        // name: expr, always after positional args
+        named: Vec<(String, ExprNode)>,
     },
 }
 
@@ -111,6 +172,20 @@ impl TryFrom<Vec<Token>> for ExprNode {
 // expression constructors
 impl ExprNode {
     // Non-atomic variants
+    pub fn and(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::And {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn or(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::Or {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
     pub fn eq(left: ExprNode, right: ExprNode) -> Self {
         ExprNode::Eq {
             left: Box::new(left),
@@ -153,6 +228,48 @@ impl ExprNode {
         }
     }
 
+    pub fn cmp(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::Cmp {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn bit_or(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::BitOr {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn bit_xor(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::BitXor {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn bit_and(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::BitAnd {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn shift_left(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::Shl {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn shift_right(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::Shr {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
     pub fn add(left: ExprNode, right: ExprNode) -> Self {
         ExprNode::Add {
             left: Box::new(left),
@@ -188,6 +305,13 @@ impl ExprNode {
         }
     }
 
+    pub fn pow(left: ExprNode, right: ExprNode) -> Self {
+        ExprNode::Pow {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
     pub fn not(expr: ExprNode) -> Self {
         ExprNode::Not(Box::new(expr))
     }
@@ -196,13 +320,36 @@ impl ExprNode {
         ExprNode::Neg(Box::new(expr))
     }
 
+    pub fn bit_not(expr: ExprNode) -> Self {
+        ExprNode::BitNot(Box::new(expr))
+    }
+
+    pub fn len(expr: ExprNode) -> Self {
+        ExprNode::Len(Box::new(expr))
+    }
+
+    pub fn index(base: ExprNode, index: ExprNode) -> Self {
+        ExprNode::Index {
+            base: Box::new(base),
+            index: Box::new(index),
+        }
+    }
+
+    pub fn slice(base: ExprNode, start: Option<ExprNode>, end: Option<ExprNode>) -> Self {
+        ExprNode::Slice {
+            base: Box::new(base),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+        }
+    }
+
     // Pathed with Lpar and Rpar
     pub fn packed(expr: ExprNode) -> Self {
         ExprNode::Packed(Box::new(expr))
     }
 
     // Call variant
-    pub fn call(name: String, args: Vec<ExprNode>) -> Self {
-        ExprNode::Call { name, args }
+    pub fn call(name: String, args: Vec<ExprNode>, named: Vec<(String, ExprNode)>) -> Self {
+        ExprNode::Call { name, args, named }
     }
 }
@@ -1,3 +1,5 @@
+use crate::types::literal::Literal;
+
 use super::expr::ExprNode;
 
 #[derive(Clone, Debug)]
@@ -6,6 +8,27 @@ pub enum Lvalue {
     Member { base: String, members: Vec<String> },
 }
 
+/// `name`, `name = default` or `...name` (collects remaining positional args into a list)
+#[derive(Clone, Debug)]
+pub struct Param {
+    pub name: String,
+    pub default: Option<ExprNode>,
+    pub variadic: bool,
+}
+
+/// Pattern of a `match` arm
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    // _
+    Wildcard,
+    Literal(Literal),
+    // Person { name, age: a } binds field `name` to `name` and field `age` to `a`
+    Model {
+        name: String,
+        bindings: Vec<(String, String)>,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum StmtNode {
     Expression {
@@ -17,15 +40,48 @@ pub enum StmtNode {
         ident: String,
         expr: ExprNode,
     },
+    Const {
+        ident: String,
+        expr: ExprNode,
+    },
+    // let (a, b) = expr;
+    LetTuple {
+        idents: Vec<String>,
+        expr: ExprNode,
+    },
     Assign {
         target: Lvalue,
         expr: ExprNode,
     },
+    // a, b = expr;
+    AssignTuple {
+        targets: Vec<Lvalue>,
+        expr: ExprNode,
+    },
     Return {
         expr: ExprNode,
     },
-    Print {
+    Throw {
+        expr: ExprNode,
+    },
+    // try { } catch ident { } finally { }, at least one of catch and finally
+    Try {
+        body: Vec<StmtNode>,
+        catch: Option<(String, Vec<StmtNode>)>,
+        finally: Option<Vec<StmtNode>>,
+    },
+    // match expr { pattern => { }, ... }, the first matching arm runs
+    Match {
         expr: ExprNode,
+        arms: Vec<(Pattern, Vec<StmtNode>)>,
+    },
+    // defer stmt; runs when the enclosing scope exits
+    Defer {
+        stmt: Box<StmtNode>,
+    },
+    // print a, b, ...;
+    Print {
+        exprs: Vec<ExprNode>,
     },
     For {
         iter: String,
@@ -50,7 +106,7 @@ pub enum StmtNode {
     },
     FuncDef {
         name: String,
-        params: Vec<String>,
+        params: Vec<Param>,
         body: Vec<StmtNode>,
     },
 
@@ -1,4 +1,9 @@
-use crate::ast::stmt::StmtNode;
+use std::fmt;
+
+use crate::{
+    ast::{expr::ExprNode, stmt::StmtNode},
+    types::literal::Literal,
+};
 
 pub fn print_stmt(node: StmtNode) {
     print_stmt_lvl(node, 0);
@@ -23,11 +28,64 @@ pub fn print_stmt_lvl(node: StmtNode, lvl: usize) {
         } => {
             println!("let {:?} = {:?};", target, expr);
         }
+        StmtNode::Const { ident, expr } => {
+            println!("const {:?} = {:?};", ident, expr);
+        }
+        StmtNode::LetTuple { idents, expr } => {
+            println!("let ({}) = {:?};", idents.join(", "), expr);
+        }
         StmtNode::Return { expr } => {
             println!("return {:?};", expr);
         }
-        StmtNode::Print { expr } => {
-            println!("print {:?};", expr);
+        StmtNode::Throw { expr } => {
+            println!("throw {:?};", expr);
+        }
+        StmtNode::Try {
+            body,
+            catch,
+            finally,
+        } => {
+            println!("try {{");
+            for stmt in body {
+                print_stmt_lvl(stmt, lvl + 1);
+            }
+            indent(lvl);
+            if let Some((ident, catch_body)) = catch {
+                println!("}} catch {} {{", ident);
+                for stmt in catch_body {
+                    print_stmt_lvl(stmt, lvl + 1);
+                }
+                indent(lvl);
+            }
+            if let Some(finally_body) = finally {
+                println!("}} finally {{");
+                for stmt in finally_body {
+                    print_stmt_lvl(stmt, lvl + 1);
+                }
+                indent(lvl);
+            }
+            println!("}}");
+        }
+        StmtNode::Match { expr, arms } => {
+            println!("match {:?} {{", expr);
+            for (pattern, body) in arms {
+                indent(lvl + 1);
+                println!("{:?} => {{", pattern);
+                for stmt in body {
+                    print_stmt_lvl(stmt, lvl + 2);
+                }
+                indent(lvl + 1);
+                println!("}}");
+            }
+            indent(lvl);
+            println!("}}");
+        }
+        StmtNode::Defer { stmt } => {
+            print!("defer ");
+            print_stmt_lvl(*stmt, 0);
+        }
+        StmtNode::Print { exprs } => {
+            println!("print {:?};", exprs);
         }
         StmtNode::For { iter, n_iter, body } => {
             println!("for {} : {:?} {{", iter, n_iter);
@@ -79,6 +137,14 @@ pub fn print_stmt_lvl(node: StmtNode, lvl: usize) {
             indent(lvl);
         }
         StmtNode::FuncDef { name, params, body } => {
+            let params: Vec<_> = params
+                .iter()
+                .map(|param| match (&param.default, param.variadic) {
+                    (_, true) => format!("...{}", param.name),
+                    (Some(default), _) => format!("{} = {:?}", param.name, default),
+                    (None, _) => param.name.clone(),
+                })
+                .collect();
             println!("func {} ({}) {{", name, params.join(","));
             for stmt in body {
                 print_stmt_lvl(stmt, lvl + 1);
@@ -98,5 +164,110 @@ pub fn print_stmt_lvl(node: StmtNode, lvl: usize) {
         StmtNode::Assign { target, expr } => {
             println!("{:?} = {:?};", target, expr);
         }
+        StmtNode::AssignTuple { targets, expr } => {
+            println!("{:?} = {:?};", targets, expr);
+        }
+    }
+}
+
+/// Source text of an expression, as written up to spacing, for messages about it
+impl fmt::Display for ExprNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let binary = |f: &mut fmt::Formatter<'_>, left: &ExprNode, op: &str, right: &ExprNode| {
+            write!(f, "{} {} {}", left, op, right)
+        };
+        let list = |f: &mut fmt::Formatter<'_>, elems: &[ExprNode]| {
+            let elems: Vec<_> = elems.iter().map(|elem| elem.to_string()).collect();
+            write!(f, "{}", elems.join(", "))
+        };
+        match self {
+            ExprNode::Literal(lit) => match lit {
+                Literal::Int(x) => write!(f, "{}", x),
+                Literal::Float(x) => write!(f, "{:?}", x),
+                Literal::String(x) => write!(f, "\"{}\"", x),
+                Literal::Bool(x) => write!(f, "{}", x),
+                Literal::Teer(x) => write!(f, "{:?}", x),
+                Literal::Nil => write!(f, "nil"),
+            },
+            ExprNode::Identifer(name) => write!(f, "{}", name),
+            ExprNode::New(typename) => write!(f, "new {}", typename),
+            ExprNode::Member { base, members } => write!(f, "{}.{}", base, members.join(".")),
+
+            ExprNode::And { left, right } => binary(f, left, "&&", right),
+            ExprNode::Or { left, right } => binary(f, left, "||", right),
+            ExprNode::Eq { left, right } => binary(f, left, "==", right),
+            ExprNode::Neq { left, right } => binary(f, left, "!=", right),
+            ExprNode::Gt { left, right } => binary(f, left, ">", right),
+            ExprNode::Lt { left, right } => binary(f, left, "<", right),
+            ExprNode::Geq { left, right } => binary(f, left, ">=", right),
+            ExprNode::Leq { left, right } => binary(f, left, "<=", right),
+            ExprNode::Cmp { left, right } => binary(f, left, "<=>", right),
+            ExprNode::BitOr { left, right } => binary(f, left, "|", right),
+            ExprNode::BitXor { left, right } => binary(f, left, "^", right),
+            ExprNode::BitAnd { left, right } => binary(f, left, "&", right),
+            ExprNode::Shl { left, right } => binary(f, left, "<<", right),
+            ExprNode::Shr { left, right } => binary(f, left, ">>", right),
+            ExprNode::Add { left, right } => binary(f, left, "+", right),
+            ExprNode::Minus { left, right } => binary(f, left, "-", right),
+            ExprNode::Div { left, right } => binary(f, left, "/", right),
+            ExprNode::Mod { left, right } => binary(f, left, "%", right),
+            ExprNode::Mul { left, right } => binary(f, left, "*", right),
+            ExprNode::Pow { left, right } => binary(f, left, "**", right),
+
+            ExprNode::Not(expr) => write!(f, "!{}", expr),
+            ExprNode::Neg(expr) => write!(f, "-{}", expr),
+            ExprNode::BitNot(expr) => write!(f, "~{}", expr),
+            ExprNode::Len(expr) => write!(f, "#{}", expr),
+
+            ExprNode::Index { base, index } => write!(f, "{}[{}]", base, index),
+            ExprNode::Slice { base, start, end } => {
+                write!(f, "{}[", base)?;
+                if let Some(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                write!(f, ":")?;
+                if let Some(end) = end {
+                    write!(f, "{}", end)?;
+                }
+                write!(f, "]")
+            }
+
+            ExprNode::Packed(expr) => write!(f, "({})", expr),
+            ExprNode::Tuple(elems) => {
+                write!(f, "(")?;
+                list(f, elems)?;
+                match elems.len() {
+                    1 => write!(f, ",)"),
+                    _ => write!(f, ")"),
+                }
+            }
+            ExprNode::List(elems) => {
+                write!(f, "[")?;
+                list(f, elems)?;
+                write!(f, "]")
+            }
+            ExprNode::Interpolated(parts) => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match part {
+                        ExprNode::Literal(Literal::String(text)) => write!(f, "{}", text)?,
+                        expr => write!(f, "{{{}}}", expr)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+
+            ExprNode::Call { name, args, named } => {
+                write!(f, "{}(", name)?;
+                list(f, args)?;
+                for (i, (arg_name, arg)) in named.iter().enumerate() {
+                    if i > 0 || !args.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", arg_name, arg)?;
+                }
+                write!(f, ")")
+            }
+        }
     }
 }
@@ -22,6 +22,15 @@ pub enum ParserError {
     #[error("Function has duplicate arguments: {}", .0)]
     DuplicateArg(String), // arg
 
+    #[error("Invalid parameter list: {}", .0)]
+    InvalidParamList(String), // message
+
+    #[error("Duplicate binding in destructuring: {}", .0)]
+    DuplicateBinding(String), // ident
+
+    #[error("Non-exhaustive match on teer: missing {}", .0)]
+    NonExhaustiveMatch(String), // missing variants
+
     #[error("Model has duplicate fields: {}", .0)]
     DuplicateField(String), // fieldname
 
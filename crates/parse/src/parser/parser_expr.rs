@@ -1,8 +1,9 @@
 use crate::{
     ast::expr::ExprNode,
     errors::{ParserError, ParserResult},
+    types::literal::Literal,
 };
-use lex::token::Token;
+use lex::{pest_parse::sl_parse_str, token::Token};
 
 /**
  * 每次成功解析一个token，就必须移动cur，移动到下一个要被解析的token的位置。
@@ -39,8 +40,51 @@ impl ExprTokens {
 /// remember to advance cur poiner
 impl ExprTokens {
     pub fn parse_expr(&mut self) -> ParserResult<ExprNode> {
-        println!("tokens: {:?}", self.tokens);
-        self.parse_equality()
+        self.parse_or()
+    }
+
+    pub fn parse_or(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_and()?;
+
+        let mut or_continue = true;
+        while or_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            or_continue = false;
+
+            if let Token::Or = self.current() {
+                or_continue = true;
+                self.cur += 1;
+                let right = self.parse_and()?;
+                left = ExprNode::or(left, right);
+                continue;
+            }
+        }
+
+        Ok(left)
+    }
+
+    pub fn parse_and(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_equality()?;
+
+        let mut and_continue = true;
+        while and_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            and_continue = false;
+
+            if let Token::And = self.current() {
+                and_continue = true;
+                self.cur += 1;
+                let right = self.parse_equality()?;
+                left = ExprNode::and(left, right);
+                continue;
+            }
+        }
+
+        Ok(left)
     }
 
     pub fn parse_equality(&mut self) -> ParserResult<ExprNode> {
@@ -56,7 +100,7 @@ impl ExprTokens {
             if let Token::Eq = self.current() {
                 eq_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_comparison()?;
                 left = ExprNode::eq(left, right);
                 continue;
             }
@@ -64,7 +108,7 @@ impl ExprTokens {
             if let Token::Neq = self.current() {
                 eq_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_comparison()?;
                 left = ExprNode::neq(left, right);
                 continue;
             }
@@ -74,7 +118,7 @@ impl ExprTokens {
     }
 
     pub fn parse_comparison(&mut self) -> ParserResult<ExprNode> {
-        let mut left = self.parse_add()?;
+        let mut left = self.parse_bit_or()?;
 
         let mut cmp_continue = true;
         while cmp_continue {
@@ -86,7 +130,7 @@ impl ExprTokens {
             if let Token::Gt = self.current() {
                 cmp_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_bit_or()?;
                 left = ExprNode::gt(left, right);
                 continue;
             }
@@ -94,7 +138,7 @@ impl ExprTokens {
             if let Token::Lt = self.current() {
                 cmp_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_bit_or()?;
                 left = ExprNode::lt(left, right);
                 continue;
             }
@@ -102,7 +146,7 @@ impl ExprTokens {
             if let Token::Geq = self.current() {
                 cmp_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_bit_or()?;
                 left = ExprNode::geq(left, right);
                 continue;
             }
@@ -110,10 +154,111 @@ impl ExprTokens {
             if let Token::Leq = self.current() {
                 cmp_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_bit_or()?;
                 left = ExprNode::leq(left, right);
                 continue;
             }
+
+            if let Token::Cmp = self.current() {
+                cmp_continue = true;
+                self.cur += 1;
+                let right = self.parse_bit_or()?;
+                left = ExprNode::cmp(left, right);
+                continue;
+            }
+        }
+        Ok(left)
+    }
+
+    /// bitwise operators bind tighter than comparisons: a & 1 == 0 is (a & 1) == 0
+    pub fn parse_bit_or(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_bit_xor()?;
+
+        let mut or_continue = true;
+        while or_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            or_continue = false;
+
+            if let Token::Pipe = self.current() {
+                or_continue = true;
+                self.cur += 1;
+                let right = self.parse_bit_xor()?;
+                left = ExprNode::bit_or(left, right);
+                continue;
+            }
+        }
+        Ok(left)
+    }
+
+    pub fn parse_bit_xor(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_bit_and()?;
+
+        let mut xor_continue = true;
+        while xor_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            xor_continue = false;
+
+            if let Token::Caret = self.current() {
+                xor_continue = true;
+                self.cur += 1;
+                let right = self.parse_bit_and()?;
+                left = ExprNode::bit_xor(left, right);
+                continue;
+            }
+        }
+        Ok(left)
+    }
+
+    pub fn parse_bit_and(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_shift()?;
+
+        let mut and_continue = true;
+        while and_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            and_continue = false;
+
+            if let Token::Amp = self.current() {
+                and_continue = true;
+                self.cur += 1;
+                let right = self.parse_shift()?;
+                left = ExprNode::bit_and(left, right);
+                continue;
+            }
+        }
+        Ok(left)
+    }
+
+    pub fn parse_shift(&mut self) -> ParserResult<ExprNode> {
+        let mut left = self.parse_add()?;
+
+        let mut shift_continue = true;
+        while shift_continue {
+            if self.is_terminal() {
+                return Ok(left);
+            }
+            shift_continue = false;
+
+            if let Token::Shl = self.current() {
+                shift_continue = true;
+                self.cur += 1;
+                let right = self.parse_add()?;
+                left = ExprNode::shift_left(left, right);
+                continue;
+            }
+
+            if let Token::Shr = self.current() {
+                shift_continue = true;
+                self.cur += 1;
+                let right = self.parse_add()?;
+                left = ExprNode::shift_right(left, right);
+                continue;
+            }
         }
         Ok(left)
     }
@@ -131,7 +276,7 @@ impl ExprTokens {
             if let Token::Plus = self.current() {
                 add_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_mul()?;
                 left = ExprNode::add(left, right);
                 continue;
             }
@@ -139,7 +284,7 @@ impl ExprTokens {
             if let Token::Minus = self.current() {
                 add_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_mul()?;
                 left = ExprNode::minus(left, right);
                 continue;
             }
@@ -160,7 +305,7 @@ impl ExprTokens {
             if let Token::Star = self.current() {
                 mul_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_unary()?;
                 left = ExprNode::mul(left, right);
                 continue;
             }
@@ -168,7 +313,7 @@ impl ExprTokens {
             if let Token::Slash = self.current() {
                 mul_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_unary()?;
                 left = ExprNode::div(left, right);
                 continue;
             }
@@ -176,7 +321,7 @@ impl ExprTokens {
             if let Token::Percent = self.current() {
                 mul_continue = true;
                 self.cur += 1;
-                let right = self.parse_add()?;
+                let right = self.parse_unary()?;
                 left = ExprNode::modulus(left, right);
                 continue;
             }
@@ -194,9 +339,83 @@ impl ExprTokens {
             self.cur += 1;
             return Ok(ExprNode::neg(self.parse_unary()?));
         }
+        if let Token::Hash = self.current() {
+            self.cur += 1;
+            return Ok(ExprNode::len(self.parse_unary()?));
+        }
+        if let Token::Tilde = self.current() {
+            self.cur += 1;
+            return Ok(ExprNode::bit_not(self.parse_unary()?));
+        }
+
+        // ... or power
+        self.parse_pow()
+    }
+
+    /// `**` binds tighter than unary operators on its left: -2 ** 2 is -(2 ** 2)
+    pub fn parse_pow(&mut self) -> ParserResult<ExprNode> {
+        let base = self.parse_postfix()?;
 
-        // ... or atom
-        self.parse_atom()
+        if !self.is_terminal() {
+            if let Token::Pow = self.current() {
+                self.cur += 1;
+                // right associative, and the exponent may be negated: 2 ** -1
+                let exp = self.parse_unary()?;
+                return Ok(ExprNode::pow(base, exp));
+            }
+        }
+        Ok(base)
+    }
+
+    /// atom followed by any number of `[index]` or `[start:end]`
+    pub fn parse_postfix(&mut self) -> ParserResult<ExprNode> {
+        let mut base = self.parse_atom()?;
+
+        while !self.is_terminal() {
+            if let Token::Lbracket = self.current() {
+                self.cur += 1;
+                let start = match self.next_nth(0)? {
+                    Token::Colon => None,
+                    _ => Some(self.parse_expr()?),
+                };
+                match self.next_nth(0)? {
+                    Token::Rbracket => {
+                        self.cur += 1;
+                        match start {
+                            Some(index) => base = ExprNode::index(base, index),
+                            None => {
+                                return Err(ParserError::InvalidExpression(
+                                    "Empty index".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    Token::Colon => {
+                        self.cur += 1;
+                        let end = match self.next_nth(0)? {
+                            Token::Rbracket => None,
+                            _ => Some(self.parse_expr()?),
+                        };
+                        if !matches!(self.next_nth(0)?, Token::Rbracket) {
+                            return Err(ParserError::InvalidSyntax(
+                                "Expected `]` after slice".to_string(),
+                            ));
+                        }
+                        self.cur += 1;
+                        base = ExprNode::slice(base, start, end);
+                    }
+                    token => {
+                        return Err(ParserError::InvalidSyntax(format!(
+                            "Unexpected token in index: {:?}",
+                            token
+                        )))
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(base)
     }
 
     pub fn parse_atom(&mut self) -> ParserResult<ExprNode> {
@@ -223,7 +442,6 @@ impl ExprTokens {
                 // iteratively parse member until there is none
                 while let Token::Dot = self.next_nth(member_offset)? {
                     if let Token::Identifier(member) = self.next_nth(member_offset + 1)? {
-                        println!("{}", member);
                         members.push(member);
                     }
                     member_offset += 2;
@@ -248,14 +466,33 @@ impl ExprTokens {
                     return Ok(ExprNode::Call {
                         name: ident,
                         args: vec![],
+                        named: vec![],
                     });
                 } else {
                     self.cur += 2;
                     let mut args = Vec::new();
+                    let mut named: Vec<(String, ExprNode)> = Vec::new();
                     'parse_args: loop {
-                        let expr = self.parse_expr()?;
-                        args.push(expr);
-                        let sep = self.current();
+                        // named arg: `name: expr`
+                        if let (Token::Identifier(arg_name), Token::Colon) =
+                            (self.next_nth(0)?, self.next_nth(1)?)
+                        {
+                            if named.iter().any(|(name, _)| *name == arg_name) {
+                                return Err(ParserError::DuplicateArg(arg_name));
+                            }
+                            self.cur += 2;
+                            let expr = self.parse_expr()?;
+                            named.push((arg_name, expr));
+                        } else {
+                            if !named.is_empty() {
+                                return Err(ParserError::InvalidSyntax(
+                                    "Positional argument after named argument".to_string(),
+                                ));
+                            }
+                            let expr = self.parse_expr()?;
+                            args.push(expr);
+                        }
+                        let sep = self.next_nth(0)?;
                         match sep {
                             Token::Comma => {
                                 self.cur += 1;
@@ -272,29 +509,75 @@ impl ExprTokens {
                             }
                         }
                     }
-                    return Ok(ExprNode::call(ident, args));
+                    return Ok(ExprNode::call(ident, args, named));
                 }
             } else {
                 self.cur += 1;
                 return Ok(ExprNode::Identifer(ident));
             }
         }
+        // list literal: [a, b, ...]
+        if let Token::Lbracket = self.current() {
+            self.cur += 1;
+            let mut elems = Vec::new();
+            loop {
+                if let Token::Rbracket = self.next_nth(0)? {
+                    self.cur += 1;
+                    return Ok(ExprNode::List(elems));
+                }
+                elems.push(self.parse_expr()?);
+                match self.next_nth(0)? {
+                    Token::Comma => self.cur += 1,
+                    Token::Rbracket => {}
+                    token => {
+                        return Err(ParserError::InvalidSyntax(format!(
+                            "Unexpected token in list: {:?}",
+                            token
+                        )))
+                    }
+                }
+            }
+        }
+        if let Token::String(s) = self.current() {
+            self.cur += 1;
+            return parse_interpolated(&s);
+        }
         if let Ok(lit) = self.current().try_into() {
             self.cur += 1;
             return Ok(ExprNode::Literal(lit));
         }
         if let Token::Lpar = self.current() {
             self.cur += 1;
-            let expr = self.parse_expr()?;
-            if let Token::Rpar = self.current() {
+            // empty tuple: ()
+            if let Token::Rpar = self.next_nth(0)? {
                 self.cur += 1;
-                return Ok(ExprNode::packed(expr));
+                return Ok(ExprNode::Tuple(vec![]));
+            }
+            let expr = self.parse_expr()?;
+            match self.next_nth(0)? {
+                Token::Rpar => {
+                    self.cur += 1;
+                    return Ok(ExprNode::packed(expr));
+                }
+                Token::Comma => {
+                    let mut elems = vec![expr];
+                    while let Token::Comma = self.next_nth(0)? {
+                        self.cur += 1;
+                        // trailing comma: (a,)
+                        if let Token::Rpar = self.next_nth(0)? {
+                            break;
+                        }
+                        elems.push(self.parse_expr()?);
+                    }
+                    if let Token::Rpar = self.next_nth(0)? {
+                        self.cur += 1;
+                        return Ok(ExprNode::Tuple(elems));
+                    }
+                }
+                _ => {}
             }
         }
 
-        dbg!(&self.tokens);
-        dbg!(&self.cur);
-
         Err(ParserError::InvalidSyntax(format!(
             "Invalid atomic expression starter: {:?}",
             self.current()
@@ -307,3 +590,67 @@ impl ExprTokens {
 }
 
 // member: ident ~ ("." ~ ident)*
+
+/// Split a string literal into text and `{expr}` parts.
+/// `{{` and `}}` are literal braces, as are a `}` or an unclosed `{` on their own,
+/// `{}` and `{:spec}` are kept as placeholders for `format`
+fn parse_interpolated(s: &str) -> ParserResult<ExprNode> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut inner = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    inner.push(c);
+                }
+                if !closed {
+                    text.push('{');
+                    text.push_str(&inner);
+                    break;
+                }
+                if inner.is_empty() || inner.starts_with(':') {
+                    text.push('{');
+                    text.push_str(&inner);
+                    text.push('}');
+                    continue;
+                }
+
+                let mut tokens: Vec<Token> = sl_parse_str(&inner)
+                    .map_err(ParserError::InvalidExpression)?
+                    .collect();
+                // drop trailing EOF
+                tokens.pop();
+                if !text.is_empty() {
+                    parts.push(ExprNode::Literal(Literal::String(std::mem::take(
+                        &mut text,
+                    ))));
+                }
+                parts.push(ExprNode::try_from(tokens)?);
+            }
+            c => text.push(c),
+        }
+    }
+
+    if parts.is_empty() {
+        return Ok(ExprNode::Literal(Literal::String(text)));
+    }
+    if !text.is_empty() {
+        parts.push(ExprNode::Literal(Literal::String(text)));
+    }
+    Ok(ExprNode::Interpolated(parts))
+}
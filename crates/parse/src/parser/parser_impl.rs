@@ -1,14 +1,15 @@
 use std::collections::BTreeSet;
 
-use lex::token::Token;
+use lex::token::{teer, Token};
 
 use crate::{
     ast::{
         expr::ExprNode,
         root::Root,
-        stmt::{Lvalue, StmtNode},
+        stmt::{Lvalue, Param, Pattern, StmtNode},
     },
     errors::{ParserError, ParserResult},
+    types::literal::Literal,
 };
 
 use super::{parser::Parser, parser_expr};
@@ -23,78 +24,86 @@ impl Parser {
         let mut statements: Vec<StmtNode> = Vec::new();
 
         while self.cur < self.tokens.len() {
-            //
-            let statement = match self.current() {
-                Token::Let => {
-                    // parse let stmt
-                    self.parse_let()
-                }
-                Token::Return => {
-                    // parse return stmt
-                    self.parse_return()
-                }
-                // function as statement
-                Token::Func => self.parse_func(),
-                Token::If => match self.parse_if() {
-                    Ok(if_step) => {
-                        // try to further parse `else`
-                        if let Token::Else = self.next_nth(if_step.step) {
-                            // if successful
-                            match self.try_parse_else(if_step.step) {
-                                // combine if step and else step
-                                Ok((else_body, else_step)) => Ok(ParserStep {
-                                    stmt: StmtNode::IfElse {
-                                        cond: if_step.stmt.unwrap_if().0,
-                                        if_body: if_step.stmt.unwrap_if().1,
-                                        else_body,
-                                    },
-                                    step: if_step.step + else_step,
-                                }),
-                                Err(e) => Err(e),
-                            }
-                        } else {
-                            dbg!(self.next_nth(if_step.step + 1));
-                            Ok(if_step)
-                        }
-                    }
-                    Err(e) => Err(e),
-                },
-                Token::Model => self.parse_model(),
-                Token::Print => self.parse_print(),
-                Token::For => self.parse_for(),
-                Token::While => self.parse_while(),
-                Token::Lbrace => self.parse_scope(),
-                Token::Eof => break,
-                Token::Identifier(_) => self.parse_ident(),
-
-                // expression statement
-                Token::Int(_) => self.parse_expression_stmt(),
-                Token::Float(_) => self.parse_expression_stmt(),
-                Token::String(_) => self.parse_expression_stmt(),
-                Token::Bool(_) => self.parse_expression_stmt(),
-                Token::Teer(_) => self.parse_expression_stmt(),
-                Token::Nil => self.parse_expression_stmt(),
-
-                // other
-                token => {
-                    return Err(ParserError::InvalidSyntax(format!(
-                        "Starting token: {:?}, Parse an 'expression statement'",
-                        token
-                    )));
-                }
-            };
-
-            match statement {
-                Ok(ParserStep { stmt, step }) => {
+            match self.parse_next()? {
+                Some(ParserStep { stmt, step }) => {
                     statements.push(stmt);
                     self.advance(step);
                 }
-                Err(err) => return Err(err),
+                None => break,
             }
         }
 
         Ok(Root { statements })
     }
+
+    /// Parse one statement at the cursor without advancing, `None` on EOF
+    fn parse_next(&self) -> ParserResult<Option<ParserStep>> {
+        let statement = match self.next_nth(0) {
+            Token::Let => {
+                // parse let stmt
+                self.parse_let()
+            }
+            Token::Const => self.parse_const(),
+            Token::Return => {
+                // parse return stmt
+                self.parse_return()
+            }
+            Token::Throw => self.parse_throw(),
+            Token::Try => self.parse_try(),
+            Token::Match => self.parse_match(),
+            Token::Defer => self.parse_defer(),
+            // function as statement
+            Token::Func => self.parse_func(),
+            Token::If => match self.parse_if() {
+                Ok(if_step) => {
+                    // try to further parse `else`
+                    if let Token::Else = self.next_nth(if_step.step) {
+                        // if successful
+                        match self.try_parse_else(if_step.step) {
+                            // combine if step and else step
+                            Ok((else_body, else_step)) => Ok(ParserStep {
+                                stmt: StmtNode::IfElse {
+                                    cond: if_step.stmt.unwrap_if().0,
+                                    if_body: if_step.stmt.unwrap_if().1,
+                                    else_body,
+                                },
+                                step: if_step.step + else_step,
+                            }),
+                            Err(e) => Err(e),
+                        }
+                    } else {
+                        Ok(if_step)
+                    }
+                }
+                Err(e) => Err(e),
+            },
+            Token::Model => self.parse_model(),
+            Token::Print => self.parse_print(),
+            Token::For => self.parse_for(),
+            Token::While => self.parse_while(),
+            Token::Lbrace => self.parse_scope(),
+            Token::Eof => return Ok(None),
+            Token::Identifier(_) => self.parse_ident(),
+
+            // expression statement
+            Token::Int(_) => self.parse_expression_stmt(),
+            Token::Float(_) => self.parse_expression_stmt(),
+            Token::String(_) | Token::RawString(_) => self.parse_expression_stmt(),
+            Token::Bool(_) => self.parse_expression_stmt(),
+            Token::Teer(_) => self.parse_expression_stmt(),
+            Token::Nil => self.parse_expression_stmt(),
+
+            // other
+            token => {
+                return Err(ParserError::InvalidSyntax(format!(
+                    "Starting token: {:?}, Parse an 'expression statement'",
+                    token
+                )));
+            }
+        };
+
+        statement.map(Some)
+    }
 }
 
 /*
@@ -171,73 +180,166 @@ impl Parser {
     // }
 
     pub fn parse_assign(&self) -> ParserResult<ParserStep> {
-        // match: let ident =
-        if let Token::Identifier(ident) = self.current() {
-            let mut member_offset = 1;
-            let mut members = Vec::new();
-            'parse_members: loop {
-                match self.next_nth(member_offset) {
-                    Token::Dot => {
-                        if let Token::Identifier(member) = self.next_nth(member_offset + 1) {
-                            members.push(member);
-                            member_offset += 2;
-                        } else {
-                            return Err(ParserError::InvalidSyntax(format!(
-                                "Expected identfier after member operator `.`"
-                            )));
-                        }
-                    }
-                    Token::Assign => break 'parse_members,
-                    token => {
+        // match: lvalue (, lvalue)* =
+        let mut targets = Vec::new();
+        let mut offset = 0;
+        loop {
+            if let Token::Identifier(ident) = self.next_nth(offset) {
+                offset += 1;
+                let mut members = Vec::new();
+                while let Token::Dot = self.next_nth(offset) {
+                    if let Token::Identifier(member) = self.next_nth(offset + 1) {
+                        members.push(member);
+                        offset += 2;
+                    } else {
                         return Err(ParserError::InvalidSyntax(format!(
-                            "Invalid member separator: {:?}",
-                            token
+                            "Expected identfier after member operator `.`"
                         )));
                     }
                 }
-            }
-
-            if let Token::Assign = self.next_nth(member_offset) {
-                // match trailing ;
-                let mut expr_tokens = Vec::new();
-                for i in member_offset + 1.. {
-                    match self.next_nth(i) {
-                        Token::Eof => {
-                            return Err(ParserError::UnexpectedEof);
-                        }
-                        Token::Semicolon => {
-                            break;
-                        }
-                        other => {
-                            expr_tokens.push(other);
-                        }
-                    }
-                }
-                // try parse intermediate tokens into expr
-                let len = expr_tokens.len();
-                let target = if members.is_empty() {
+                targets.push(if members.is_empty() {
                     Lvalue::Identifier(ident)
                 } else {
                     Lvalue::Member {
                         base: ident,
                         members,
                     }
-                };
-                return Ok(ParserStep {
-                    stmt: StmtNode::Assign {
-                        target,
-                        expr: ExprNode::try_from(expr_tokens)?,
-                    },
-                    // let <> = <>;
-                    step: member_offset + len + 2,
                 });
+            } else {
+                return Err(ParserError::InvalidSyntax(
+                    "Expected identifier as assignment target".to_string(),
+                ));
+            }
+
+            match self.next_nth(offset) {
+                Token::Comma => offset += 1,
+                Token::Assign => break,
+                token => {
+                    return Err(ParserError::InvalidSyntax(format!(
+                        "Invalid member separator: {:?}",
+                        token
+                    )));
+                }
             }
         }
 
-        Err(crate::errors::ParserError::InvalidSyntax("Let".to_string()))
+        // match trailing ;
+        let mut expr_tokens = Vec::new();
+        for i in offset + 1.. {
+            match self.next_nth(i) {
+                Token::Eof => {
+                    return Err(ParserError::UnexpectedEof);
+                }
+                Token::Semicolon => {
+                    break;
+                }
+                other => {
+                    expr_tokens.push(other);
+                }
+            }
+        }
+        // try parse intermediate tokens into expr
+        let len = expr_tokens.len();
+        let expr = parse_expr_list(expr_tokens)?;
+        let stmt = if targets.len() == 1 {
+            StmtNode::Assign {
+                target: targets.remove(0),
+                expr,
+            }
+        } else {
+            StmtNode::AssignTuple { targets, expr }
+        };
+        Ok(ParserStep {
+            stmt,
+            // <> = <>;
+            step: offset + len + 2,
+        })
     }
 
     pub fn parse_let(&self) -> ParserResult<ParserStep> {
+        if let Token::Lpar = self.next_nth(1) {
+            return self.parse_let_tuple();
+        }
+        let (ident, expr, step) = self.parse_binding("Let")?;
+        Ok(ParserStep {
+            stmt: StmtNode::Let { ident, expr },
+            step,
+        })
+    }
+
+    pub fn parse_const(&self) -> ParserResult<ParserStep> {
+        let (ident, expr, step) = self.parse_binding("Const")?;
+        Ok(ParserStep {
+            stmt: StmtNode::Const { ident, expr },
+            step,
+        })
+    }
+
+    /// let (a, b, ...) = expr;
+    pub fn parse_let_tuple(&self) -> ParserResult<ParserStep> {
+        let mut idents: Vec<String> = Vec::new();
+        let mut ipeek = 2;
+        loop {
+            match self.next_nth(ipeek) {
+                Token::Identifier(ident) => {
+                    if idents.contains(&ident) {
+                        return Err(ParserError::DuplicateBinding(ident));
+                    }
+                    idents.push(ident);
+                }
+                token => {
+                    return Err(ParserError::InvalidSyntax(format!(
+                        "Expected identifier in destructuring, got {:?}",
+                        token
+                    )))
+                }
+            }
+            match self.next_nth(ipeek + 1) {
+                Token::Comma => ipeek += 2,
+                Token::Rpar => {
+                    ipeek += 2;
+                    break;
+                }
+                sep => {
+                    return Err(ParserError::InvalidSyntax(format!(
+                        "Expected comma or rpar, got {:?}",
+                        sep
+                    )))
+                }
+            }
+        }
+
+        if let Token::Assign = self.next_nth(ipeek) {
+            // match trailing ;
+            let mut expr_tokens = Vec::new();
+            for i in ipeek + 1.. {
+                match self.next_nth(i) {
+                    Token::Eof => {
+                        return Err(ParserError::UnexpectedEof);
+                    }
+                    Token::Semicolon => {
+                        break;
+                    }
+                    other => {
+                        expr_tokens.push(other);
+                    }
+                }
+            }
+            let len = expr_tokens.len();
+            return Ok(ParserStep {
+                stmt: StmtNode::LetTuple {
+                    idents,
+                    expr: parse_expr_list(expr_tokens)?,
+                },
+                // let (<>) = <>;
+                step: ipeek + len + 2,
+            });
+        }
+        Err(ParserError::InvalidSyntax("Let".to_string()))
+    }
+
+    /// `<keyword> ident = expr;`, shared by let and const
+    fn parse_binding(&self, keyword: &str) -> ParserResult<(String, ExprNode, usize)> {
         // match: let ident =
         if let (Token::Identifier(ident), Token::Assign) = (self.next_nth(1), self.next_nth(2)) {
             // match trailing ;
@@ -257,15 +359,11 @@ impl Parser {
             }
             // try parse intermediate tokens into expr
             let len = expr_tokens.len();
-            return Ok(ParserStep {
-                stmt: StmtNode::Let {
-                    ident,
-                    expr: ExprNode::try_from(expr_tokens)?,
-                },
-                step: 4 + len,
-            });
+            return Ok((ident, parse_expr_list(expr_tokens)?, 4 + len));
         }
-        Err(crate::errors::ParserError::InvalidSyntax("Let".to_string()))
+        Err(crate::errors::ParserError::InvalidSyntax(
+            keyword.to_string(),
+        ))
     }
 
     pub fn parse_for(&self) -> ParserResult<ParserStep> {
@@ -508,7 +606,7 @@ impl Parser {
 
         // try parse intermediate tokens into expr
         let len = expr_tokens.len();
-        if let Ok(expr) = expr_tokens.try_into() {
+        if let Ok(expr) = parse_expr_list(expr_tokens) {
             return Ok(ParserStep {
                 stmt: StmtNode::Return { expr },
                 step: 2 + len,
@@ -520,6 +618,271 @@ impl Parser {
         ))
     }
 
+    pub fn parse_throw(&self) -> ParserResult<ParserStep> {
+        // match trailing ;
+        let mut expr_tokens = Vec::new();
+        for i in 1.. {
+            match self.next_nth(i) {
+                Token::Eof => {
+                    return Err(ParserError::UnexpectedEof);
+                }
+                Token::Semicolon => {
+                    break;
+                }
+                other => {
+                    expr_tokens.push(other);
+                }
+            }
+        }
+
+        let len = expr_tokens.len();
+        Ok(ParserStep {
+            stmt: StmtNode::Throw {
+                expr: parse_expr_list(expr_tokens)?,
+            },
+            step: 2 + len,
+        })
+    }
+
+    pub fn parse_try(&self) -> ParserResult<ParserStep> {
+        let (body, mut ipeek) = self.parse_block(1)?;
+
+        let mut catch = None;
+        if let Token::Catch = self.next_nth(ipeek) {
+            if let Token::Identifier(ident) = self.next_nth(ipeek + 1) {
+                let (catch_body, next) = self.parse_block(ipeek + 2)?;
+                catch = Some((ident, catch_body));
+                ipeek = next;
+            } else {
+                return Err(ParserError::InvalidSyntax(
+                    "Expected identifier after `catch`".to_string(),
+                ));
+            }
+        }
+
+        let mut finally = None;
+        if let Token::Finally = self.next_nth(ipeek) {
+            let (finally_body, next) = self.parse_block(ipeek + 1)?;
+            finally = Some(finally_body);
+            ipeek = next;
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::InvalidSyntax(
+                "Try needs `catch` or `finally`".to_string(),
+            ));
+        }
+
+        Ok(ParserStep {
+            stmt: StmtNode::Try {
+                body,
+                catch,
+                finally,
+            },
+            step: ipeek,
+        })
+    }
+
+    pub fn parse_defer(&self) -> ParserResult<ParserStep> {
+        // the deferred statement is the single statement following `defer`, it ends with a `;`
+        // or a `}` outside of brackets, unless `else`, `catch` or `finally` continue it
+        let mut depth = 0;
+        let mut ipeek = 1;
+        loop {
+            match self.next_nth(ipeek) {
+                Token::Eof => return Err(ParserError::UnexpectedEof),
+                Token::Lbrace | Token::Lbracket | Token::Lpar => depth += 1,
+                Token::Rbracket | Token::Rpar => depth -= 1,
+                Token::Rbrace => {
+                    depth -= 1;
+                    let continued = matches!(
+                        self.next_nth(ipeek + 1),
+                        Token::Else | Token::Catch | Token::Finally
+                    );
+                    if depth == 0 && !continued {
+                        break;
+                    }
+                }
+                Token::Semicolon if depth == 0 => break,
+                _ => {}
+            }
+            ipeek += 1;
+        }
+        let deferred = Parser::new(self.tokens[self.cur + 1..=self.cur + ipeek].to_vec());
+        match deferred.parse_next()? {
+            Some(ParserStep { stmt, step }) => Ok(ParserStep {
+                stmt: StmtNode::Defer {
+                    stmt: Box::new(stmt),
+                },
+                step: step + 1,
+            }),
+            None => Err(ParserError::UnexpectedEof),
+        }
+    }
+
+    pub fn parse_match(&self) -> ParserResult<ParserStep> {
+        // the matched expression runs until the `{` opening the arms
+        let mut expr_tokens = Vec::new();
+        let mut ipeek = 1;
+        loop {
+            match self.next_nth(ipeek) {
+                Token::Lbrace => break,
+                Token::Eof => return Err(ParserError::UnexpectedEof),
+                token => expr_tokens.push(token),
+            }
+            ipeek += 1;
+        }
+        if expr_tokens.is_empty() {
+            return Err(ParserError::InvalidSyntax(
+                "Match needs an expression".to_string(),
+            ));
+        }
+        let expr = expr_tokens.try_into()?;
+        ipeek += 1;
+
+        let mut arms = Vec::new();
+        loop {
+            match self.next_nth(ipeek) {
+                Token::Rbrace => break,
+                Token::Eof => return Err(ParserError::UnexpectedEof),
+                // arms may be separated by commas
+                Token::Comma if !arms.is_empty() => {
+                    ipeek += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            let (pattern, next) = self.parse_pattern(ipeek)?;
+            if !matches!(self.next_nth(next), Token::FatArrow) {
+                return Err(ParserError::InvalidSyntax(format!(
+                    "Expected `=>` after match pattern, got {:?}",
+                    self.next_nth(next)
+                )));
+            }
+            let (body, next) = self.parse_block(next + 1)?;
+            arms.push((pattern, body));
+            ipeek = next;
+        }
+
+        check_teer_exhaustive(&arms)?;
+        Ok(ParserStep {
+            stmt: StmtNode::Match { expr, arms },
+            step: ipeek + 1, // + Rbrace
+        })
+    }
+
+    /// Parse `_`, a literal or `Model { field, field: ident }` at offset `start`,
+    /// returns the pattern and the offset after it
+    fn parse_pattern(&self, start: usize) -> ParserResult<(Pattern, usize)> {
+        match self.next_nth(start) {
+            Token::Identifier(ident) if ident == "_" => Ok((Pattern::Wildcard, start + 1)),
+            Token::Identifier(name) => {
+                if !matches!(self.next_nth(start + 1), Token::Lbrace) {
+                    return Err(ParserError::InvalidSyntax(format!(
+                        "Expected `{{` after model name {} in pattern",
+                        name
+                    )));
+                }
+                let mut ipeek = start + 2;
+                let mut bindings = Vec::new();
+                let mut bound = BTreeSet::new();
+                loop {
+                    if let Token::Rbrace = self.next_nth(ipeek) {
+                        return Ok((Pattern::Model { name, bindings }, ipeek + 1));
+                    }
+                    let field = match self.next_nth(ipeek) {
+                        Token::Identifier(field) => field,
+                        Token::Eof => return Err(ParserError::UnexpectedEof),
+                        token => {
+                            return Err(ParserError::InvalidSyntax(format!(
+                                "Expected field name in model pattern, got {:?}",
+                                token
+                            )))
+                        }
+                    };
+                    ipeek += 1;
+
+                    // `field: ident` binds the field to another name
+                    let ident = if let Token::Colon = self.next_nth(ipeek) {
+                        if let Token::Identifier(ident) = self.next_nth(ipeek + 1) {
+                            ipeek += 2;
+                            ident
+                        } else {
+                            return Err(ParserError::InvalidSyntax(
+                                "Expected identifier after `:` in model pattern".to_string(),
+                            ));
+                        }
+                    } else {
+                        field.clone()
+                    };
+                    if !bound.insert(ident.clone()) {
+                        return Err(ParserError::DuplicateBinding(ident));
+                    }
+                    bindings.push((field, ident));
+
+                    match self.next_nth(ipeek) {
+                        Token::Comma => ipeek += 1,
+                        Token::Rbrace => {}
+                        token => {
+                            return Err(ParserError::InvalidSyntax(format!(
+                                "Expected `,` or `}}` in model pattern, got {:?}",
+                                token
+                            )))
+                        }
+                    }
+                }
+            }
+            // negative numbers are lexed as minus and a literal
+            Token::Minus => match self.next_nth(start + 1) {
+                Token::Int(x) => Ok((Pattern::Literal(Literal::Int(-x)), start + 2)),
+                Token::Float(x) => Ok((Pattern::Literal(Literal::Float(-x)), start + 2)),
+                token => Err(ParserError::NotLiteral(token)),
+            },
+            token => Ok((Pattern::Literal(token.try_into()?), start + 1)),
+        }
+    }
+
+    /// Parse `{ stmts }` where `{` is at offset `start`,
+    /// returns the statements and the offset after `}`
+    pub fn parse_block(&self, start: usize) -> ParserResult<(Vec<StmtNode>, usize)> {
+        if !matches!(self.next_nth(start), Token::Lbrace) {
+            return Err(ParserError::InvalidSyntax(format!(
+                "Expected `{{`, got {:?}",
+                self.next_nth(start)
+            )));
+        }
+        // read until }, get body
+        // we must match {} while peeking token forward
+        let mut n_lbr = 1;
+        let mut body_tokens = Vec::new();
+        for i in start + 1.. {
+            match self.next_nth(i) {
+                // get EOF before }
+                Token::Eof => {
+                    return Err(ParserError::UnexpectedEof);
+                }
+                other => {
+                    match other {
+                        Token::Lbrace => {
+                            n_lbr += 1;
+                        }
+                        Token::Rbrace => {
+                            n_lbr -= 1;
+                            if n_lbr == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    body_tokens.push(other);
+                }
+            }
+        }
+        let len = body_tokens.len();
+        let body = Parser::new(body_tokens).parse_stmt()?.statements;
+        Ok((body, start + len + 2))
+    }
+
     pub fn parse_print(&self) -> ParserResult<ParserStep> {
         // match trailing ;
         let mut expr_tokens = Vec::new();
@@ -537,54 +900,59 @@ impl Parser {
             }
         }
 
-        // try parse intermediate tokens into expr
+        // comma-separated args
         let len = expr_tokens.len();
-        if let Ok(expr) = expr_tokens.try_into() {
-            return Ok(ParserStep {
-                stmt: StmtNode::Print { expr },
-                step: 2 + len,
-            });
+        let mut exprs = Vec::new();
+        for part in split_top_level(expr_tokens) {
+            if part.is_empty() {
+                return Err(ParserError::InvalidSyntax("Print".to_string()));
+            }
+            exprs.push(part.try_into()?);
         }
-
-        Err(crate::errors::ParserError::InvalidSyntax(
-            "Print".to_string(),
-        ))
+        Ok(ParserStep {
+            stmt: StmtNode::Print { exprs },
+            step: 2 + len,
+        })
     }
 
     pub fn parse_func(&self) -> ParserResult<ParserStep> {
         if let (Token::Identifier(name), Token::Lpar) = (self.next_nth(1), self.next_nth(2)) {
             // no param
             let mut ipeek = 3;
-            let mut params = Vec::new();
+            let mut params: Vec<Param> = Vec::new();
             // parse param list
             loop {
-                match self.next_nth(ipeek) {
-                    Token::Identifier(param) => {
-                        if params.contains(&param) {
-                            return Err(ParserError::DuplicateArg(param));
-                        } else {
-                            params.push(param);
-                        }
-                    }
-                    Token::Rpar => {
-                        ipeek += 1;
-                        break;
+                if let Token::Rpar = self.next_nth(ipeek) {
+                    ipeek += 1;
+                    break;
+                }
+                let (param, next) = self.parse_param(ipeek)?;
+                if params.iter().any(|p| p.name == param.name) {
+                    return Err(ParserError::DuplicateArg(param.name));
+                }
+                if let Some(last) = params.last() {
+                    if last.variadic {
+                        return Err(ParserError::InvalidParamList(format!(
+                            "variadic parameter `{}` must be the last one",
+                            last.name
+                        )));
                     }
-                    token => {
-                        return Err(ParserError::InvalidSyntax(format!(
-                            "Expected identifier of rpar, got {:?}",
-                            token
-                        )))
+                    if last.default.is_some() && param.default.is_none() && !param.variadic {
+                        return Err(ParserError::InvalidParamList(format!(
+                            "parameter `{}` without default follows a parameter with default",
+                            param.name
+                        )));
                     }
                 }
-                let sep = self.next_nth(ipeek + 1);
+                params.push(param);
+                ipeek = next;
 
-                match sep {
+                match self.next_nth(ipeek) {
                     Token::Comma => {
-                        ipeek += 2;
+                        ipeek += 1;
                     }
                     Token::Rpar => {
-                        ipeek += 2;
+                        ipeek += 1;
                         break;
                     }
                     sep => {
@@ -634,6 +1002,73 @@ impl Parser {
         Err(ParserError::InvalidSyntax("Func def".to_string()))
     }
 
+    /// `name`, `name = expr` or `...name` starting at offset `start`,
+    /// returns the param and the offset of the token after it
+    fn parse_param(&self, start: usize) -> ParserResult<(Param, usize)> {
+        let mut ipeek = start;
+        let variadic = if let Token::Ellipsis = self.next_nth(ipeek) {
+            ipeek += 1;
+            true
+        } else {
+            false
+        };
+        let name = match self.next_nth(ipeek) {
+            Token::Identifier(name) => name,
+            token => {
+                return Err(ParserError::InvalidSyntax(format!(
+                    "Expected identifier of rpar, got {:?}",
+                    token
+                )))
+            }
+        };
+        ipeek += 1;
+
+        // default value, read until `,` or `)` outside brackets
+        let mut default = None;
+        if let Token::Assign = self.next_nth(ipeek) {
+            if variadic {
+                return Err(ParserError::InvalidParamList(format!(
+                    "variadic parameter `{}` cannot have a default",
+                    name
+                )));
+            }
+            ipeek += 1;
+            let mut depth = 0;
+            let mut default_tokens = Vec::new();
+            loop {
+                match self.next_nth(ipeek) {
+                    Token::Eof => return Err(ParserError::UnexpectedEof),
+                    Token::Comma | Token::Rpar if depth == 0 => break,
+                    token => {
+                        match token {
+                            Token::Lpar | Token::Lbracket | Token::Lbrace => depth += 1,
+                            Token::Rpar | Token::Rbracket | Token::Rbrace => depth -= 1,
+                            _ => {}
+                        }
+                        default_tokens.push(token);
+                    }
+                }
+                ipeek += 1;
+            }
+            if default_tokens.is_empty() {
+                return Err(ParserError::InvalidParamList(format!(
+                    "missing default value of `{}`",
+                    name
+                )));
+            }
+            default = Some(ExprNode::try_from(default_tokens)?);
+        }
+
+        Ok((
+            Param {
+                name,
+                default,
+                variadic,
+            },
+            ipeek,
+        ))
+    }
+
     fn parse_model(&self) -> ParserResult<ParserStep> {
         if let (Token::Identifier(model_name), Token::Lbrace) = (self.next_nth(1), self.next_nth(2))
         {
@@ -719,3 +1154,66 @@ impl Parser {
         ))
     }
 }
+
+/// A match with a teer arm must cover excel, empty and exile or have a wildcard
+fn check_teer_exhaustive(arms: &[(Pattern, Vec<StmtNode>)]) -> ParserResult<()> {
+    let mut covered = Vec::new();
+    for (pattern, _) in arms {
+        match pattern {
+            Pattern::Wildcard => return Ok(()),
+            Pattern::Literal(Literal::Teer(t)) => covered.push(*t),
+            _ => {}
+        }
+    }
+    if covered.is_empty() {
+        return Ok(());
+    }
+
+    let missing: Vec<_> = [teer::excel, teer::empty, teer::exile]
+        .into_iter()
+        .filter(|t| !covered.contains(t))
+        .map(|t| format!("{:?}", t))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ParserError::NonExhaustiveMatch(missing.join(", ")))
+    }
+}
+
+/// Split tokens on commas that are not nested in any brackets
+fn split_top_level(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0;
+    for token in tokens {
+        match token {
+            Token::Lpar | Token::Lbracket | Token::Lbrace => depth += 1,
+            Token::Rpar | Token::Rbracket | Token::Rbrace => depth -= 1,
+            Token::Comma if depth == 0 => {
+                parts.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        parts.last_mut().unwrap().push(token);
+    }
+    parts
+}
+
+/// An expression, or a tuple when there are top-level commas: `a, b` is `(a, b)`
+fn parse_expr_list(tokens: Vec<Token>) -> ParserResult<ExprNode> {
+    let mut parts = split_top_level(tokens);
+    if parts.len() == 1 {
+        return ExprNode::try_from(parts.remove(0));
+    }
+    let mut elems = Vec::new();
+    for part in parts {
+        if part.is_empty() {
+            return Err(ParserError::InvalidExpression(
+                "Empty element in tuple".to_string(),
+            ));
+        }
+        elems.push(ExprNode::try_from(part)?);
+    }
+    Ok(ExprNode::Tuple(elems))
+}
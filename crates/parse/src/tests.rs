@@ -1,9 +1,11 @@
 use std::{fs, io::Write};
 
-use lex::pest_parse::sl_parse_file;
+use lex::pest_parse::{sl_parse_file, sl_parse_str};
 use log::{error, info};
 
-use crate::{display::print_stmt, parser::parser::Parser};
+use crate::{
+    ast::expr::ExprNode, display::print_stmt, errors::ParserError, parser::parser::Parser,
+};
 
 #[test]
 fn test_base() {
@@ -73,3 +75,32 @@ fn test_exec_1() {
     file.write_all(format!("{:?}", root.statements).as_bytes())
         .unwrap();
 }
+
+#[test]
+fn test_param_order() {
+    let tokens = sl_parse_file("../../test_sources/params_order.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt();
+    assert!(matches!(root, Err(ParserError::InvalidParamList(_))));
+}
+
+#[test]
+fn test_match_teer_exhaustive() {
+    let tokens = sl_parse_file("../../test_sources/match_teer.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt();
+    assert!(matches!(root, Err(ParserError::NonExhaustiveMatch(m)) if m == "empty"));
+}
+
+#[test]
+fn test_display_expr() {
+    let tokens: Vec<_> = sl_parse_str(
+        "f(-a[1:] ** 2, (b + 1) * c, [x.y, #s], \"n = {n}\", (1,), check: !ok && t <=> excel)",
+    )
+    .unwrap()
+    .collect();
+    // drop trailing EOF
+    let expr = ExprNode::try_from(tokens[..tokens.len() - 1].to_vec()).unwrap();
+    assert_eq!(
+        expr.to_string(),
+        "f(-a[1:] ** 2, (b + 1) * c, [x.y, #s], \"n = {n}\", (1,), check: !ok && t <=> excel)"
+    );
+}
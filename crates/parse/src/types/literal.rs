@@ -19,7 +19,7 @@ impl TryFrom<Token> for Literal {
         match value {
             Token::Int(x) => Ok(Literal::Int(x)),
             Token::Float(x) => Ok(Literal::Float(x)),
-            Token::String(x) => Ok(Literal::String(x)),
+            Token::String(x) | Token::RawString(x) => Ok(Literal::String(x)),
             Token::Bool(x) => Ok(Literal::Bool(x)),
             Token::Teer(x) => Ok(Literal::Teer(x)),
             Token::Nil => Ok(Literal::Nil),
@@ -0,0 +1,76 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::errors::{TwiError, TwiResult};
+
+/// What a program may touch outside the interpreter, nothing by default
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    /// files under this directory may be read and written, relative paths start here
+    pub fs_root: Option<PathBuf>,
+    /// `read_line` may read standard input
+    pub stdin: bool,
+}
+
+impl Capabilities {
+    pub(crate) fn check_stdin(&self) -> TwiResult<()> {
+        match self.stdin {
+            true => Ok(()),
+            false => Err(TwiError::PermissionDenied(
+                "reading stdin is not allowed, run with --allow-stdin".into(),
+            )),
+        }
+    }
+
+    /// `path` resolved against the fs root, as long as it stays inside the root.
+    /// `..` is resolved by name, and symlinks are refused, so that neither an existing
+    /// link nor a dangling one leads outside. The file itself need not exist.
+    pub(crate) fn resolve(&self, path: &str) -> TwiResult<PathBuf> {
+        let root = self.fs_root.as_ref().ok_or_else(|| {
+            TwiError::PermissionDenied(
+                "file access is not allowed, run with --allow-fs=<dir>".into(),
+            )
+        })?;
+        let root = root.canonicalize().map_err(io_error(root))?;
+        let outside =
+            || TwiError::PermissionDenied(format!("{} is outside {}", path, root.display()));
+
+        let relative = match Path::new(path).strip_prefix(&root) {
+            Ok(relative) => relative,
+            Err(_) if Path::new(path).is_absolute() => return Err(outside()),
+            Err(_) => Path::new(path),
+        };
+
+        let mut resolved = root.clone();
+        for component in relative.components() {
+            match component {
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    let is_link = fs::symlink_metadata(&resolved)
+                        .is_ok_and(|meta| meta.file_type().is_symlink());
+                    if is_link {
+                        return Err(TwiError::PermissionDenied(format!(
+                            "{} is a symbolic link",
+                            resolved.display()
+                        )));
+                    }
+                }
+                Component::ParentDir => {
+                    resolved.pop();
+                    if !resolved.starts_with(&root) {
+                        return Err(outside());
+                    }
+                }
+                Component::CurDir => {}
+                Component::RootDir | Component::Prefix(_) => return Err(outside()),
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+pub(crate) fn io_error(path: &Path) -> impl Fn(std::io::Error) -> TwiError + '_ {
+    move |e| TwiError::Io(format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,222 @@
+use lex::token::teer;
+use num_bigint::BigInt;
+
+use crate::{
+    errors::{TwiError, TwiResult},
+    runtime::gc::value::Value,
+};
+
+/// Rust values that can be passed to SL
+pub trait ToSl {
+    fn to_sl(self) -> Value;
+}
+
+/// Rust values that SL values can be read as
+pub trait FromSl: Sized {
+    fn from_sl(val: Value) -> TwiResult<Self>;
+}
+
+fn unexpected<T>(expected: &str, val: Value) -> TwiResult<T> {
+    Err(TwiError::UnexpectedType {
+        expected: expected.into(),
+        got: val.to_string(),
+    })
+}
+
+impl ToSl for Value {
+    fn to_sl(self) -> Value {
+        self
+    }
+}
+
+impl FromSl for Value {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        Ok(val)
+    }
+}
+
+impl ToSl for () {
+    fn to_sl(self) -> Value {
+        Value::Nil
+    }
+}
+
+impl FromSl for () {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Nil => Ok(()),
+            val => unexpected("Nil", val),
+        }
+    }
+}
+
+impl ToSl for i64 {
+    fn to_sl(self) -> Value {
+        Value::Int(self)
+    }
+}
+
+impl FromSl for i64 {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Int(x) => Ok(x),
+            val => unexpected("Int", val),
+        }
+    }
+}
+
+/// Results that fit in an `Int` are `Int`s, see `Runtime::alloc_int`
+impl ToSl for BigInt {
+    fn to_sl(self) -> Value {
+        match i64::try_from(&self) {
+            Ok(x) => Value::Int(x),
+            Err(_) => Value::BigInt(self),
+        }
+    }
+}
+
+impl FromSl for BigInt {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Int(x) => Ok(x.into()),
+            Value::BigInt(x) => Ok(x),
+            val => unexpected("Int", val),
+        }
+    }
+}
+
+impl ToSl for f64 {
+    fn to_sl(self) -> Value {
+        Value::Float(self)
+    }
+}
+
+/// ints are read as floats too
+impl FromSl for f64 {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Float(x) => Ok(x),
+            Value::Int(x) => Ok(x as f64),
+            val => unexpected("Float", val),
+        }
+    }
+}
+
+impl ToSl for bool {
+    fn to_sl(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl FromSl for bool {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Bool(b) => Ok(b),
+            val => unexpected("Bool", val),
+        }
+    }
+}
+
+impl ToSl for teer {
+    fn to_sl(self) -> Value {
+        Value::Teer(self)
+    }
+}
+
+impl FromSl for teer {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Teer(t) => Ok(t),
+            val => unexpected("Teer", val),
+        }
+    }
+}
+
+impl ToSl for String {
+    fn to_sl(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl ToSl for &str {
+    fn to_sl(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl FromSl for String {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::String(s) => Ok(s),
+            val => unexpected("String", val),
+        }
+    }
+}
+
+/// `None` is nil
+impl<T: ToSl> ToSl for Option<T> {
+    fn to_sl(self) -> Value {
+        match self {
+            Some(x) => x.to_sl(),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl<T: FromSl> FromSl for Option<T> {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::Nil => Ok(None),
+            val => T::from_sl(val).map(Some),
+        }
+    }
+}
+
+/// Vecs are lists, and are read from lists or tuples
+impl<T: ToSl> ToSl for Vec<T> {
+    fn to_sl(self) -> Value {
+        Value::List(self.into_iter().map(ToSl::to_sl).collect())
+    }
+}
+
+impl<T: FromSl> FromSl for Vec<T> {
+    fn from_sl(val: Value) -> TwiResult<Self> {
+        match val {
+            Value::List(elems) | Value::Tuple(elems) => elems.into_iter().map(T::from_sl).collect(),
+            val => unexpected("List", val),
+        }
+    }
+}
+
+macro_rules! tuple_sl {
+    ($n:expr; $($t:ident),+) => {
+        impl<$($t: ToSl),+> ToSl for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn to_sl(self) -> Value {
+                let ($($t,)+) = self;
+                Value::Tuple(vec![$($t.to_sl()),+])
+            }
+        }
+
+        impl<$($t: FromSl),+> FromSl for ($($t,)+) {
+            fn from_sl(val: Value) -> TwiResult<Self> {
+                match val {
+                    Value::Tuple(elems) if elems.len() == $n => {
+                        let mut elems = elems.into_iter();
+                        Ok(($($t::from_sl(elems.next().unwrap())?,)+))
+                    }
+                    Value::Tuple(elems) => Err(TwiError::TupleArityMismatch {
+                        expected: $n,
+                        got: elems.len(),
+                    }),
+                    val => unexpected("Tuple", val),
+                }
+            }
+        }
+    };
+}
+
+tuple_sl!(1; A);
+tuple_sl!(2; A, B);
+tuple_sl!(3; A, B, C);
+tuple_sl!(4; A, B, C, D);
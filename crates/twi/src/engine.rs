@@ -0,0 +1,161 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use lex::pest_parse::sl_parse_str;
+use parse::parser::parser::Parser;
+
+use crate::{
+    capabilities::Capabilities,
+    convert::{FromSl, ToSl},
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{objects::Object, persistent::Persistent, value::Value},
+        runtime::runtime::Runtime,
+    },
+    scope::scope::ScopeType,
+};
+
+/// The interpreter embedded in a Rust program: load SL source, call its functions
+/// and exchange values with Rust. Errors are returned, the process is never exited.
+pub struct Engine {
+    rt: Runtime,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        let mut rt = Runtime::structure(Vec::new(), true).expect("an empty program is valid");
+        rt.set_plain(true);
+        Self { rt }
+    }
+
+    /// Send what the program prints to `output` instead of stdout,
+    /// see [`crate::CapturedOutput`]
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.rt.set_output(output);
+    }
+
+    /// Read `read_line` input from `input` instead of stdin
+    pub fn set_input(&mut self, input: impl BufRead + 'static) {
+        self.rt.set_input(input);
+    }
+
+    /// See [`Runtime::set_max_call_depth`]
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.rt.set_max_call_depth(depth);
+    }
+
+    /// Allow the I/O builtins what `caps` allows, they are all denied by default
+    pub fn set_capabilities(&mut self, caps: Capabilities) {
+        self.rt.set_capabilities(caps);
+    }
+
+    /// Run the top-level statements of `source` in the global scope, as in script mode.
+    /// Functions, models and globals stay defined for later loads and calls.
+    pub fn load_str(&mut self, source: &str) -> TwiResult<()> {
+        let tokens = sl_parse_str(source).map_err(TwiError::Syntax)?;
+        let root = Parser::new_from_iter(tokens)
+            .parse_stmt()
+            .map_err(|e| TwiError::Syntax(e.to_string()))?;
+        self.rt.load(root.statements)
+    }
+
+    pub fn load_path(&mut self, path: impl AsRef<Path>) -> TwiResult<()> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| TwiError::Io(format!("{}: {}", path.display(), e)))?;
+        self.load_str(&source)
+    }
+
+    /// Call the global function `name` with positional args, e.g.
+    /// `engine.call::<i64>("add", vec![1.to_sl(), 2.to_sl()])`
+    pub fn call<R: FromSl>(&mut self, name: &str, args: Vec<Value>) -> TwiResult<R> {
+        let val = self.call_then(name, args, Runtime::value)?;
+        R::from_sl(val)
+    }
+
+    /// Like [`Engine::call`], but the result is kept as an object
+    /// that stays alive while the handle exists
+    pub fn call_persistent(&mut self, name: &str, args: Vec<Value>) -> TwiResult<Persistent> {
+        self.call_then(name, args, Runtime::persist)
+    }
+
+    /// The args are kept alive by a scope around the call,
+    /// `then` reads the result before the scope exits
+    fn call_then<T>(
+        &mut self,
+        name: &str,
+        args: Vec<Value>,
+        then: fn(&Runtime, Object) -> T,
+    ) -> TwiResult<T> {
+        let callable = self.global(name)?;
+
+        self.rt.backtrace = None;
+        let sg = self.rt.enter_scope(ScopeType::Block);
+        let args = args
+            .into_iter()
+            .map(|arg| self.rt.alloc_value(arg))
+            .collect();
+        let res = self
+            .rt
+            .call_object(name.to_string(), None, callable, args, Vec::new())
+            .map(|obj| then(&self.rt, obj));
+        sg.exit(res)
+    }
+
+    pub fn get_global<T: FromSl>(&self, name: &str) -> TwiResult<T> {
+        let obj = self.global(name)?;
+        T::from_sl(self.rt.value(obj))
+    }
+
+    /// Bind a global, replacing any previous value unless it is a `const`
+    pub fn set_global<T: ToSl>(&mut self, name: &str, val: T) -> TwiResult<()> {
+        if self.rt.global_scope.consts.contains(name) {
+            return Err(TwiError::AssignToConst(name.to_string()));
+        }
+        let sg = self.rt.enter_scope(ScopeType::Block);
+        let obj = self.rt.alloc_value(val.to_sl());
+        self.rt.global_scope.add(name.to_string(), obj);
+        sg.exit(Ok(()))
+    }
+
+    /// See [`Runtime::register_native`]
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, func: F)
+    where
+        F: Fn(&mut Runtime, &[Object]) -> TwiResult<Object> + 'static,
+    {
+        self.rt.register_native(name, arity, func);
+    }
+
+    /// Value of an object handed out by the engine, such as the one in [`TwiError::Throw`].
+    /// Objects are only valid until the engine runs SL code again, unless persisted.
+    pub fn value(&self, obj: Object) -> Value {
+        self.rt.value(obj)
+    }
+
+    /// Keep `obj` alive across calls while the handle exists
+    pub fn persist(&self, obj: Object) -> Persistent {
+        self.rt.persist(obj)
+    }
+
+    /// Free every object not reachable from the globals or a persistent handle.
+    /// Collection also happens on its own while SL code runs.
+    pub fn collect_garbage(&mut self) {
+        self.rt.gc();
+    }
+
+    fn global(&self, name: &str) -> TwiResult<Object> {
+        self.rt
+            .global_scope
+            .get(name)
+            .ok_or_else(|| TwiError::IdentifierNotFound(name.to_string()))
+    }
+}
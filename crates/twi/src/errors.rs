@@ -34,6 +34,16 @@ pub enum TwiError {
     #[error("Incompatible unary operation type: {}", .0)]
     IncompatibleUnopType(String),
 
+    // ############### loading ###############
+    #[error("Syntax error: {}", .0)]
+    Syntax(String), // message
+
+    #[error("I/O error: {}", .0)]
+    Io(String), // message
+
+    #[error("Permission denied: {}", .0)]
+    PermissionDenied(String), // message
+
     // ############### others ###############
     #[error("Invalid global definition: {} is not allowed in global scope", .0)]
     InvalidGlobalDefinition(String),
@@ -42,6 +52,9 @@ pub enum TwiError {
     #[error("Duplicate local binding: {}", .0)]
     DuplicateLocalBind(String), // ident
 
+    #[error("Cannot assign to constant: {}", .0)]
+    AssignToConst(String), // ident
+
     #[error("Value of unexpected type: expected {}, got {}", expected, got)]
     UnexpectedType { expected: String, got: String },
 
@@ -56,15 +69,129 @@ pub enum TwiError {
     )]
     ArgNumMismatch {
         funcname: String,
-        expected: usize,
+        expected: String, // from `arg_count`
         got: usize,
     },
 
+    #[error("Tuple arity mismatch: expected {} elements, got {}", expected, got)]
+    TupleArityMismatch { expected: usize, got: usize },
+
+    #[error("In function {}: missing argument for parameter {}", funcname, param)]
+    MissingArg { funcname: String, param: String },
+
+    #[error("In function {}: no parameter named {}", funcname, name)]
+    UnknownNamedArg { funcname: String, name: String },
+
+    #[error("In function {}: argument {} given more than once", funcname, param)]
+    DuplicateArgBinding { funcname: String, param: String },
+
+    #[error("In function {}: invalid argument: {}", funcname, message)]
+    InvalidArgument { funcname: String, message: String },
+
+    #[error("Cannot convert {} to {}", value, target)]
+    InvalidConversion { value: String, target: String },
+
+    #[error("No match arm for value: {}", .0)]
+    NoMatchingArm(String), // value
+
+    #[error("Index out of bounds: index {}, length {}", index, len)]
+    IndexOutOfBounds { index: usize, len: usize },
+
+    #[error("Slice out of bounds: {}..{}, length {}", start, end, len)]
+    SliceOutOfBounds { start: usize, end: usize, len: usize },
+
+    #[error("Invalid format string: {}", .0)]
+    InvalidFormat(String), // message
+
+    #[error("Format string has {} placeholders, got {} args", expected, got)]
+    FormatArgNumMismatch { expected: usize, got: usize },
+
+    #[error("Integer overflow: {}", .0)]
+    Overflow(String), // operation
+
+    #[error("Negative shift amount: {}", .0)]
+    NegativeShift(i64), // amount
+
+    #[error(
+        "Stack overflow: more than {} nested calls, in function {}",
+        depth,
+        funcname
+    )]
+    StackOverflow { funcname: String, depth: usize },
+
     #[error("Division by zero")]
     DivisionByZero,
 
     #[error("Return value")]
     Return(Object),
+
+    #[error("Uncaught exception")]
+    Throw(Object),
+}
+
+impl TwiError {
+    /// Variant name, e.g. `DivisionByZero`, exposed to scripts as the `kind` of a caught error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TwiError::MainNotFound => "MainNotFound",
+            TwiError::TypeNotFound(_) => "TypeNotFound",
+            TwiError::IdentifierNotFound(_) => "IdentifierNotFound",
+            TwiError::ModelNotFound(_) => "ModelNotFound",
+            TwiError::MemberNotFound(_) => "MemberNotFound",
+            TwiError::CannotGetMember(_) => "CannotGetMember",
+            TwiError::CannotCall(_) => "CannotCall",
+            TwiError::IncompatibleBinopType { .. } => "IncompatibleBinopType",
+            TwiError::IncompatibleUnopType(_) => "IncompatibleUnopType",
+            TwiError::Syntax(_) => "Syntax",
+            TwiError::Io(_) => "Io",
+            TwiError::PermissionDenied(_) => "PermissionDenied",
+            TwiError::InvalidGlobalDefinition(_) => "InvalidGlobalDefinition",
+            TwiError::DuplicateLocalBind(_) => "DuplicateLocalBind",
+            TwiError::AssignToConst(_) => "AssignToConst",
+            TwiError::UnexpectedType { .. } => "UnexpectedType",
+            TwiError::UnexpectedStatement(_) => "UnexpectedStatement",
+            TwiError::ArgNumMismatch { .. } => "ArgNumMismatch",
+            TwiError::TupleArityMismatch { .. } => "TupleArityMismatch",
+            TwiError::MissingArg { .. } => "MissingArg",
+            TwiError::UnknownNamedArg { .. } => "UnknownNamedArg",
+            TwiError::DuplicateArgBinding { .. } => "DuplicateArgBinding",
+            TwiError::InvalidArgument { .. } => "InvalidArgument",
+            TwiError::InvalidConversion { .. } => "InvalidConversion",
+            TwiError::NoMatchingArm(_) => "NoMatchingArm",
+            TwiError::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+            TwiError::SliceOutOfBounds { .. } => "SliceOutOfBounds",
+            TwiError::InvalidFormat(_) => "InvalidFormat",
+            TwiError::FormatArgNumMismatch { .. } => "FormatArgNumMismatch",
+            TwiError::Overflow(_) => "Overflow",
+            TwiError::NegativeShift(_) => "NegativeShift",
+            TwiError::StackOverflow { .. } => "StackOverflow",
+            TwiError::DivisionByZero => "DivisionByZero",
+            TwiError::Return(_) => "Return",
+            TwiError::Throw(_) => "Throw",
+        }
+    }
+
+    /// Errors the program itself can raise can be caught by `try`, while control flow
+    /// and errors in loading the program or in the interpreter cannot
+    pub fn is_catchable(&self) -> bool {
+        !matches!(
+            self,
+            TwiError::MainNotFound
+                | TwiError::Syntax(_)
+                | TwiError::InvalidGlobalDefinition(_)
+                | TwiError::UnexpectedStatement(_)
+                | TwiError::Return(_)
+        )
+    }
+}
+
+/// Number of args a call takes, given the fewest and the most, if there is a most
+pub(crate) fn arg_count(min: usize, max: Option<usize>) -> String {
+    match max {
+        Some(max) if max == min => min.to_string(),
+        Some(max) => format!("{} to {}", min, max),
+        None => format!("at least {}", min),
+    }
 }
 
 pub type TwiResult<T> = Result<T, TwiError>;
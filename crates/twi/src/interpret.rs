@@ -3,5 +3,5 @@ use twi::{run_program, InterpreterConfig};
 
 fn main() {
     let cfg = InterpreterConfig::parse();
-    run_program(cfg);
+    std::process::exit(run_program(cfg));
 }
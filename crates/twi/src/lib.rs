@@ -1,86 +1,206 @@
 // Rustc please remind me of unused scopeguard
 #![forbid(unused_must_use)]
-mod errors;
+pub mod capabilities;
+pub mod convert;
+pub mod engine;
+pub mod errors;
+pub mod output;
+mod repl;
 mod runtime;
 mod scope;
 #[cfg(test)]
 mod tests;
 
+pub use capabilities::Capabilities;
+pub use convert::{FromSl, ToSl};
+pub use engine::Engine;
+pub use errors::{TwiError, TwiResult};
+pub use output::CapturedOutput;
+pub use runtime::{
+    gc::{objects::Object, persistent::Persistent, value::Value},
+    runtime::{runtime::Runtime, runtime_impl::DEFAULT_MAX_CALL_DEPTH},
+};
+
 // ###################################################
 // ################ Interface Wrapper ################
 // ###################################################
 
 use clap::Parser as ArgParser;
 use colored::Colorize;
-use errors::TwiError;
-use lex::pest_parse::sl_parse_file;
+use lex::pest_parse::sl_parse_str;
 use parse::parser::parser::Parser;
-use runtime::runtime::runtime::Runtime;
 
 #[derive(ArgParser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct InterpreterConfig {
+    /// Program to run, also taken from the first trailing arg, a REPL starts without one
     #[arg(short, long)]
-    path: String,
+    path: Option<String>,
 
     #[arg(short('i'), long, default_value_t = 0.8)]
     gc_interval: f64,
+
+    /// Run top-level statements in order instead of calling `main`
+    #[arg(short, long)]
+    script: bool,
+
+    /// How deep nested values are printed before eliding them
+    #[arg(short, long, default_value_t = 8)]
+    display_depth: usize,
+
+    /// Print only the program's output, without prefixes or banners
+    #[arg(long)]
+    plain: bool,
+
+    /// Let the program read and write files under this directory
+    #[arg(long, value_name = "DIR")]
+    allow_fs: Option<std::path::PathBuf>,
+
+    /// Let the program read standard input
+    #[arg(long)]
+    allow_stdin: bool,
+
+    /// Calls that may be in progress at once before a `StackOverflow` error
+    #[arg(long, default_value_t = DEFAULT_MAX_CALL_DEPTH)]
+    max_call_depth: usize,
+
+    /// Passed to `func main(args)` as a list of strings, after the program path
+    /// unless it is given with --path
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
 }
 
 impl InterpreterConfig {
     pub fn default(path: &str) -> Self {
         Self {
-            path: path.to_string(),
+            path: Some(path.to_string()),
             gc_interval: 0.8,
+            script: false,
+            display_depth: 8,
+            plain: false,
+            allow_fs: None,
+            allow_stdin: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            args: Vec::new(),
         }
     }
 }
 
-pub fn run_program(cfg: InterpreterConfig) {
+/// Exit code of a program that failed to compile or to set up
+pub const EXIT_COMPILE_ERROR: i32 = 2;
+/// Exit code of a program stopped by a runtime error
+pub const EXIT_RUNTIME_ERROR: i32 = 3;
+/// Exit code of a program stopped by an uncaught `throw`
+pub const EXIT_UNCAUGHT: i32 = 4;
+
+/// Run the program, or a REPL without a path, and return its exit code: the `Int`
+/// from 0 to 255 the program returns, 0 if it returns a non-int, or an `EXIT_*` code
+/// if it fails or returns an int out of that range
+pub fn run_program(mut cfg: InterpreterConfig) -> i32 {
+    if cfg.path.is_none() && !cfg.args.is_empty() {
+        cfg.path = Some(cfg.args.remove(0));
+    }
+    let Some(path) = &cfg.path else {
+        return repl::run_repl(&cfg);
+    };
+
     std::env::set_var("RUST_LOG", "trace");
-    pretty_env_logger::init();
+    let _ = pretty_env_logger::try_init();
 
-    let tokens = sl_parse_file(&cfg.path);
+    let tokens = std::fs::read_to_string(path)
+        .map_err(|e| format!("{}: {}", path, e))
+        .and_then(|source| sl_parse_str(&source));
+    let tokens = match tokens {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("\n[Compiler information] Invalid syntax:\n{}\n", e);
+            return EXIT_COMPILE_ERROR;
+        }
+    };
     let root = Parser::new_from_iter(tokens).parse_stmt();
     let root = match root {
         Ok(r) => r,
         Err(e) => {
             eprintln!("\n[Compiler information] Failed to compile: {}\n", e);
-            std::process::exit(0);
+            return EXIT_COMPILE_ERROR;
         }
     };
 
-    let rt = Runtime::try_new(root, cfg.gc_interval);
+    let rt = Runtime::try_new(root, cfg.gc_interval, cfg.script);
     let mut rt = match rt {
         Ok(rt) => rt,
         Err(e) => {
             eprintln!("Preprocess error: {:?}", e);
-            std::process::exit(0);
+            return EXIT_COMPILE_ERROR;
         }
     };
 
-    println!("\n{}\n", "[SL info] Program started".green());
+    rt.display_depth = cfg.display_depth;
+    rt.set_plain(cfg.plain);
+    rt.set_capabilities(Capabilities {
+        fs_root: cfg.allow_fs.clone(),
+        stdin: cfg.allow_stdin,
+    });
+    rt.set_max_call_depth(cfg.max_call_depth);
+    rt.set_args(cfg.args.clone());
+
+    // banners go to stderr, so stdout holds only what the program prints
+    let banner = |msg: String| {
+        if !cfg.plain {
+            eprintln!("{}", msg);
+        }
+    };
+
+    banner(format!("\n{}\n", "[SL info] Program started".green()));
     let result = rt.run();
     match result {
         Ok(_) => {
             let msg = format!("\n[SL return]\n>>  Nil\n>>  Program ended.",);
-            println!("{}", msg.blue());
-            std::process::exit(0);
+            banner(msg.blue().to_string());
+            0
         }
         Err(TwiError::Return(val)) => {
             let msg = format!(
                 "\n[SL return]\n>>  {}\n>>  Program returned.",
-                rt.heap.get_value(val)
+                rt.display(val)
             );
-            println!("{}", msg.blue());
-            std::process::exit(0);
+            banner(msg.blue().to_string());
+            let code = match rt.value(val) {
+                Value::Int(code) => u8::try_from(code).map_err(|_| code.to_string()),
+                Value::BigInt(code) => Err(code.to_string()),
+                _ => Ok(0),
+            };
+            code.map(i32::from).unwrap_or_else(|code| {
+                let msg = format!("exit code {} is out of range 0 to 255", code);
+                eprintln!("{}", msg.red());
+                EXIT_RUNTIME_ERROR
+            })
+        }
+        Err(TwiError::Throw(val)) => {
+            let msg = format!(
+                "\n[SL uncaught exception]\n>>  {}\n{}>>  Program aborted.",
+                rt.display(val),
+                backtrace(&rt)
+            );
+            eprintln!("{}", msg.red());
+            EXIT_UNCAUGHT
         }
         Err(e) => {
-            let msg = format!("\n[SL runtime error]\n>>  {}\n>>  Program aborted.", e);
-            println!("{}", msg.red());
-            std::process::exit(0);
+            let msg = format!(
+                "\n[SL runtime error]\n>>  {}\n{}>>  Program aborted.",
+                e,
+                backtrace(&rt)
+            );
+            eprintln!("{}", msg.red());
+            EXIT_RUNTIME_ERROR
         }
     }
-    // dbg!(rt.scopes);
-    // dbg!(rt.heap);
+}
+
+/// Lines of the backtrace of the error that stopped the program
+fn backtrace(rt: &Runtime) -> String {
+    rt.backtrace_lines()
+        .iter()
+        .map(|line| format!(">>    {}\n", line))
+        .collect()
 }
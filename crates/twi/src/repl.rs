@@ -0,0 +1,221 @@
+use lex::{pest_parse::sl_parse_str, token::Token};
+use parse::{
+    ast::{root::Root, stmt::StmtNode},
+    parser::parser::Parser,
+};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use crate::{
+    capabilities::Capabilities,
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{objects::Object, value::Value},
+        runtime::runtime::Runtime,
+    },
+    scope::scope::ScopeType,
+    InterpreterConfig,
+};
+
+const HELP: &str = "\
+statements, definitions and expressions are run as they are entered,
+input with unclosed braces continues on the next line
+
+:ast <source>     show the syntax tree of <source>
+:tokens <source>  show the tokens of <source>
+:heap             show the live objects
+:help             show this message
+:quit             leave, as does Ctrl-D";
+
+/// One runtime kept across inputs, as in script mode
+pub(crate) struct Repl {
+    pub(crate) rt: Runtime,
+}
+
+impl Repl {
+    pub(crate) fn new(cfg: &InterpreterConfig) -> Self {
+        let mut rt = Runtime::structure(Vec::new(), true).expect("an empty program is valid");
+        rt.gc_interval = cfg.gc_interval;
+        rt.display_depth = cfg.display_depth;
+        rt.set_max_call_depth(cfg.max_call_depth);
+        rt.set_plain(true);
+        rt.set_capabilities(Capabilities {
+            fs_root: cfg.allow_fs.clone(),
+            stdin: cfg.allow_stdin,
+        });
+        Self { rt }
+    }
+
+    /// An error as shown to the user, with the calls it was raised in
+    /// unless it was raised by the input itself
+    pub(crate) fn describe(&self, e: &TwiError) -> String {
+        let mut lines = vec![match e {
+            TwiError::Throw(val) => format!("uncaught exception: {}", self.rt.display(*val)),
+            e => e.to_string(),
+        }];
+        let backtrace = self.rt.backtrace_lines();
+        if backtrace.len() > 1 {
+            lines.extend(backtrace.iter().map(|line| format!("    {}", line)));
+        }
+        lines.join("\n")
+    }
+
+    /// Whether `source` has unclosed braces, brackets, parens, strings or comments,
+    /// and more lines should be read before running it
+    pub(crate) fn is_incomplete(source: &str) -> bool {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut in_comment = false;
+        let mut line_start = true;
+        let mut rest = source;
+        while let Some(c) = rest.chars().next() {
+            let mut len = c.len_utf8();
+            match c {
+                '/' if in_comment && rest.starts_with("//>") => {
+                    in_comment = false;
+                    len = 3;
+                }
+                _ if in_comment => {}
+                // the escaped char cannot end the string
+                '\\' if in_string => len += rest[1..].chars().next().map_or(0, char::len_utf8),
+                '"' => in_string = !in_string,
+                _ if in_string => {}
+                '<' if rest.starts_with("<//") => {
+                    in_comment = true;
+                    len = 3;
+                }
+                '#' if line_start => len = rest.find('\n').unwrap_or(rest.len()),
+                '{' | '[' | '(' => depth += 1,
+                '}' | ']' | ')' => depth -= 1,
+                _ => {}
+            }
+            line_start = match c {
+                '\n' => true,
+                ' ' | '\t' => line_start,
+                _ => false,
+            };
+            rest = &rest[len..];
+        }
+        in_string || in_comment || depth > 0
+    }
+
+    /// Run one complete input, the text to show is the value of a trailing
+    /// expression unless it is nil, or the output of a command
+    pub(crate) fn eval(&mut self, input: &str) -> TwiResult<Option<String>> {
+        let input = input.trim();
+        if let Some(command) = input.strip_prefix(':') {
+            return self.command(command).map(Some);
+        }
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        let Root { mut statements } = parse(input)?;
+        let expr = match statements.pop() {
+            Some(StmtNode::Expression { expr }) => Some(expr),
+            Some(stmt) => {
+                statements.push(stmt);
+                None
+            }
+            None => None,
+        };
+        self.rt.load(statements)?;
+        let Some(expr) = expr else {
+            return Ok(None);
+        };
+
+        let sg = self.rt.enter_scope(ScopeType::Block);
+        let res = self.rt.eval(expr);
+        self.rt.record_backtrace(&res);
+        let res = res.map(|obj| match self.rt.value(obj) {
+            Value::Nil => None,
+            _ => Some(self.rt.display(obj)),
+        });
+        sg.exit(res)
+    }
+
+    fn command(&mut self, command: &str) -> TwiResult<String> {
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        match name {
+            "ast" => Ok(format!("{:#?}", parse(arg)?.statements)),
+            "tokens" => {
+                let tokens: Vec<Token> = sl_parse_str(arg).map_err(TwiError::Syntax)?.collect();
+                Ok(format!("{:?}", tokens))
+            }
+            "heap" => Ok(self.heap()),
+            "help" => Ok(HELP.to_string()),
+            _ => Err(TwiError::Syntax(format!(
+                "unknown command :{}, see :help",
+                name
+            ))),
+        }
+    }
+
+    fn heap(&self) -> String {
+        let live: Vec<_> = (0..self.rt.heap.objs.len())
+            .filter(|&hid| self.rt.heap.objs[hid].is_some())
+            .map(|hid| format!("#{:<5} {}", hid, self.rt.display(Object { hid })))
+            .collect();
+        format!("{} live objects\n{}", live.len(), live.join("\n"))
+    }
+}
+
+/// An expression entered on its own may leave out the `;`
+fn parse(source: &str) -> TwiResult<Root> {
+    let source = source.trim();
+    let source = match source.ends_with(';') || source.ends_with('}') {
+        true => source.to_string(),
+        false => format!("{};", source),
+    };
+    let tokens = sl_parse_str(&source).map_err(TwiError::Syntax)?;
+    Parser::new_from_iter(tokens)
+        .parse_stmt()
+        .map_err(|e| TwiError::Syntax(e.to_string()))
+}
+
+/// Read inputs until `:quit` or the end of input
+pub(crate) fn run_repl(cfg: &InterpreterConfig) -> i32 {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("Cannot start the REPL: {}", e);
+            return crate::EXIT_RUNTIME_ERROR;
+        }
+    };
+    let mut repl = Repl::new(cfg);
+    println!("SL REPL, :help for commands");
+
+    let mut input = String::new();
+    loop {
+        let prompt = if input.is_empty() { "sl> " } else { "... " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                input.push_str(&line);
+                input.push('\n');
+            }
+            // Ctrl-C drops the unfinished input
+            Err(ReadlineError::Interrupted) => {
+                input.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => return 0,
+            Err(e) => {
+                eprintln!("{}", e);
+                return crate::EXIT_RUNTIME_ERROR;
+            }
+        }
+        if Repl::is_incomplete(&input) {
+            continue;
+        }
+
+        let _ = editor.add_history_entry(input.trim());
+        if input.trim() == ":quit" {
+            return 0;
+        }
+        match repl.eval(&input) {
+            Ok(Some(text)) => println!("{}", text),
+            Ok(None) => {}
+            Err(e) => eprintln!("{}", repl.describe(&e)),
+        }
+        input.clear();
+    }
+}
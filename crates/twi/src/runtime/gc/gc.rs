@@ -2,6 +2,7 @@ use crate::errors::{TwiError, TwiResult};
 
 use super::{
     objects::{self, Object, ObjectHandle, ObjectInner},
+    persistent::{Persistent, PersistentRoots},
     value::Value,
 };
 use lifo::{Deque, Lifo};
@@ -18,6 +19,8 @@ pub struct Heap {
     pub(crate) free: BTreeSet<usize>,
     pub(crate) objs: Vec<Option<ObjectHandle>>,
     pub(crate) col: Collector,
+    /// objects held by the host through `Persistent` handles, always roots
+    pub(crate) persistent: PersistentRoots,
 }
 
 impl Heap {
@@ -26,9 +29,15 @@ impl Heap {
             free: BTreeSet::new(),
             objs: Vec::new(),
             col: Collector::new(),
+            persistent: PersistentRoots::default(),
         }
     }
 
+    /// Root `obj` until the returned handle and its clones are dropped
+    pub fn persist(&self, obj: Object) -> Persistent {
+        Persistent::new(obj, self.persistent.clone())
+    }
+
     pub fn get_value(&self, obj: Object) -> Value {
         // dbg!(obj.hid);
         //
@@ -42,6 +51,7 @@ impl Heap {
         match obj_inner {
             ObjectInner::Nil => Value::Nil,
             ObjectInner::Int(x) => Value::Int(*x),
+            ObjectInner::BigInt(x) => Value::BigInt(x.clone()),
             ObjectInner::Float(x) => Value::Float(*x),
             ObjectInner::Teer(x) => Value::Teer(*x),
             ObjectInner::Bool(x) => Value::Bool(*x),
@@ -51,28 +61,54 @@ impl Heap {
                 hid: obj.hid,
                 body: body.clone(),
             },
+            ObjectInner::Native(native) => Value::Native {
+                hid: obj.hid,
+                native: native.clone(),
+            },
             ObjectInner::Model { model_name, fields } => Value::Model {
                 name: model_name.clone(),
                 hid: obj.hid,
                 members: fields
                     .iter()
                     .map(|(field_name, field_obj)| {
-                        let handle = self.objs[field_obj.hid].as_ref().unwrap();
-                        let obj_inner = unsafe { &*handle.ptr };
-                        let val = match obj_inner {
-                            ObjectInner::Model {
-                                model_name,
-                                fields: _,
-                            } => Value::ModelRef {
-                                name: model_name.clone(),
-                                hid: field_obj.hid,
-                            },
-                            _ => self.get_value(*field_obj),
-                        };
-                        (field_name.clone(), val)
+                        (field_name.clone(), self.get_ref_value(*field_obj))
                     })
                     .collect(),
             },
+            ObjectInner::Tuple(elems) => {
+                Value::Tuple(elems.iter().map(|&elem| self.get_ref_value(elem)).collect())
+            }
+            ObjectInner::List(elems) => {
+                Value::List(elems.iter().map(|&elem| self.get_ref_value(elem)).collect())
+            }
+        }
+    }
+
+    /// Value of an object held by another object: models are not expanded
+    fn get_ref_value(&self, obj: Object) -> Value {
+        let handle = self.objs[obj.hid].as_ref().unwrap();
+        let obj_inner = unsafe { &*handle.ptr };
+        match obj_inner {
+            ObjectInner::Model {
+                model_name,
+                fields: _,
+            } => Value::ModelRef {
+                name: model_name.clone(),
+                hid: obj.hid,
+            },
+            _ => self.get_value(obj),
+        }
+    }
+
+    /// Elements of a tuple or a list
+    pub fn elements(&self, obj: Object) -> TwiResult<Vec<Object>> {
+        let handle = self.objs[obj.hid].as_ref().unwrap();
+        match unsafe { &*handle.ptr } {
+            ObjectInner::Tuple(elems) | ObjectInner::List(elems) => Ok(elems.clone()),
+            _ => Err(TwiError::UnexpectedType {
+                expected: "Tuple or List".into(),
+                got: self.get_value(obj).to_string(),
+            }),
         }
     }
 
@@ -122,6 +158,9 @@ impl Heap {
     pub fn gc(&mut self, roots: Vec<Object>) {
         // mark
         self.col = Collector::from(roots);
+        for &obj in self.persistent.borrow().keys() {
+            self.col.push(obj);
+        }
         while let Some(cur) = self.col.pop() {
             cur.trace(self);
         }
@@ -164,14 +203,21 @@ impl Object {
             &*objhandle.ptr
         };
         // dbg!(&objref);
-        if let ObjectInner::Model {
-            model_name: _,
-            fields,
-        } = objref
-        {
-            for &field in fields.values() {
-                heap.col.push(field);
+        match objref {
+            ObjectInner::Model {
+                model_name: _,
+                fields,
+            } => {
+                for &field in fields.values() {
+                    heap.col.push(field);
+                }
+            }
+            ObjectInner::Tuple(elems) | ObjectInner::List(elems) => {
+                for &elem in elems {
+                    heap.col.push(elem);
+                }
             }
+            _ => {}
         }
     }
 
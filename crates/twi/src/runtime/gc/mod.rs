@@ -1,5 +1,6 @@
 pub mod gc;
 pub mod objects;
+pub mod persistent;
 #[cfg(test)]
 pub mod tests;
 pub mod value;
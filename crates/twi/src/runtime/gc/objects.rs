@@ -1,6 +1,9 @@
 use lex::token::teer;
-use parse::ast::stmt::StmtNode;
-use std::collections::BTreeMap;
+use num_bigint::BigInt;
+use parse::ast::stmt::{Param, StmtNode};
+use std::{collections::BTreeMap, fmt::Debug, rc::Rc};
+
+use crate::{errors::TwiResult, runtime::runtime::runtime::Runtime};
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Object {
@@ -25,16 +28,45 @@ pub struct ObjectHandle {
 pub enum ObjectInner {
     Nil,
     Int(i64),
+    /// integers that do not fit in an `Int`
+    BigInt(BigInt),
     Float(f64),
     Teer(teer),
     Bool(bool),
     String(String),
     Func {
-        params: Vec<String>,
+        params: Vec<Param>,
         body: Vec<StmtNode>,
     },
+    Native(NativeFunc),
     Model {
         model_name: String,
         fields: BTreeMap<String, Object>,
     },
+    Tuple(Vec<Object>),
+    List(Vec<Object>),
+}
+
+/// Functions implemented in Rust, called with evaluated args
+pub type NativeFn = Rc<dyn Fn(&mut Runtime, &[Object]) -> TwiResult<Object>>;
+
+/// A function implemented in Rust, from the standard library or registered by the host
+#[derive(Clone)]
+pub struct NativeFunc {
+    pub(crate) name: String,
+    /// number of fixed args
+    pub(crate) arity: usize,
+    /// whether any number of args may follow the fixed ones
+    pub(crate) variadic: bool,
+    pub(crate) func: NativeFn,
+}
+
+impl Debug for NativeFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunc")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("variadic", &self.variadic)
+            .finish_non_exhaustive()
+    }
 }
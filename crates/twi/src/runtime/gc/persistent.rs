@@ -0,0 +1,48 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug, rc::Rc};
+
+use super::objects::Object;
+
+/// Objects rooted by the host, with the number of handles to each
+pub(crate) type PersistentRoots = Rc<RefCell<BTreeMap<Object, usize>>>;
+
+/// Handle that keeps an object alive across collections
+/// for as long as it or any of its clones exists
+pub struct Persistent {
+    obj: Object,
+    roots: PersistentRoots,
+}
+
+impl Persistent {
+    pub(crate) fn new(obj: Object, roots: PersistentRoots) -> Self {
+        *roots.borrow_mut().entry(obj).or_insert(0) += 1;
+        Self { obj, roots }
+    }
+
+    pub fn object(&self) -> Object {
+        self.obj
+    }
+}
+
+impl Clone for Persistent {
+    fn clone(&self) -> Self {
+        Self::new(self.obj, self.roots.clone())
+    }
+}
+
+impl Drop for Persistent {
+    fn drop(&mut self) {
+        let mut roots = self.roots.borrow_mut();
+        if let Some(count) = roots.get_mut(&self.obj) {
+            *count -= 1;
+            if *count == 0 {
+                roots.remove(&self.obj);
+            }
+        }
+    }
+}
+
+impl Debug for Persistent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Persistent").field(&self.obj).finish()
+    }
+}
@@ -80,3 +80,32 @@ fn test_gc_cyc() {
     assert!(iter.next().unwrap().is_none());
     assert!(iter.next().unwrap().is_none());
 }
+
+#[test]
+fn test_gc_persistent() {
+    let mut hp = Heap::new();
+
+    let a = hp.alloc(ObjectInner::Int(1));
+    let b = hp.alloc(ObjectInner::Model {
+        model_name: "Mdl".into(),
+        fields: vec![("hello".to_string(), a)].into_iter().collect(),
+    });
+    let c = hp.alloc(ObjectInner::Nil);
+
+    let handle = hp.persist(b);
+    let cloned = handle.clone();
+    hp.gc(vec![]);
+    // held objects and what they refer to survive without other roots
+    assert!(hp.objs[a.hid].is_some());
+    assert!(hp.objs[b.hid].is_some());
+    assert!(hp.objs[c.hid].is_none());
+
+    drop(handle);
+    hp.gc(vec![]);
+    assert!(hp.objs[b.hid].is_some());
+
+    drop(cloned);
+    hp.gc(vec![]);
+    assert!(hp.objs[a.hid].is_none());
+    assert!(hp.objs[b.hid].is_none());
+}
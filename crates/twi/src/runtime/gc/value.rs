@@ -1,21 +1,29 @@
 use std::{collections::BTreeMap, fmt::Display};
 
 use lex::token::teer;
-use parse::ast::stmt::StmtNode;
+use num_bigint::BigInt;
+use parse::ast::stmt::{Param, StmtNode};
+
+use super::objects::NativeFunc;
 
 #[derive(Clone, Debug)]
 pub enum Value {
     Nil,
     Int(i64),
+    BigInt(BigInt),
     Float(f64),
     Teer(teer),
     Bool(bool),
     String(String),
     Func {
-        params: Vec<String>,
+        params: Vec<Param>,
         hid: usize,
         body: Vec<StmtNode>,
     },
+    Native {
+        hid: usize,
+        native: NativeFunc,
+    },
     Model {
         name: String,
         hid: usize,
@@ -25,10 +33,73 @@ pub enum Value {
         name: String,
         hid: usize,
     },
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+}
+
+/// `(a, b = .., ...rest)`
+pub(crate) fn fmt_params(params: &[Param]) -> String {
+    let params: Vec<_> = params
+        .iter()
+        .map(|param| match (&param.default, param.variadic) {
+            (_, true) => format!("...{}", param.name),
+            (Some(_), _) => format!("{} = ..", param.name),
+            (None, _) => param.name.clone(),
+        })
+        .collect();
+    format!("({})", params.join(", "))
+}
+
+/// Elements separated by commas, a one-element tuple keeps its trailing comma
+pub(crate) fn fmt_elems(elems: &[String], is_tuple: bool) -> String {
+    if is_tuple {
+        if elems.len() == 1 {
+            format!("({},)", elems[0])
+        } else {
+            format!("({})", elems.join(", "))
+        }
+    } else {
+        format!("[{}]", elems.join(", "))
+    }
+}
+
+/// Model instance as in SL source: `Person { name: "x", age: 3 }`
+pub(crate) fn fmt_model(name: &str, fields: &[(String, String)]) -> String {
+    if fields.is_empty() {
+        return format!("{} {{}}", name);
+    }
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(field, val)| format!("{}: {}", field, val))
+        .collect();
+    format!("{} {{ {} }}", name, fields.join(", "))
 }
 
+/// SL syntax, models held by other values are shown by reference as `<Person#hid>`
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Int(x) => write!(f, "{}", x),
+            Value::BigInt(x) => write!(f, "{}", x),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Teer(x) => write!(f, "{:?}", x),
+            Value::Bool(x) => write!(f, "{}", x),
+            Value::String(x) => write!(f, "{:?}", x),
+            Value::Func { params, .. } => write!(f, "<func{}>", fmt_params(params)),
+            Value::Native { native, .. } => write!(f, "<native {}>", native.name),
+            Value::Model { name, members, .. } => {
+                let fields: Vec<_> = members
+                    .iter()
+                    .map(|(field, val)| (field.clone(), val.to_string()))
+                    .collect();
+                write!(f, "{}", fmt_model(name, &fields))
+            }
+            Value::ModelRef { name, hid } => write!(f, "<{}#{}>", name, hid),
+            Value::Tuple(elems) | Value::List(elems) => {
+                let elems: Vec<_> = elems.iter().map(|elem| elem.to_string()).collect();
+                write!(f, "{}", fmt_elems(&elems, matches!(self, Value::Tuple(_))))
+            }
+        }
     }
 }
@@ -25,14 +25,25 @@ impl Runtime {
             }
 
             // boolean
+            ExprNode::And { left, right } => self.eval_and(*left, *right),
+            ExprNode::Or { left, right } => self.eval_or(*left, *right),
             ExprNode::Eq { left, right } => self.eval_eq(*left, *right),
             ExprNode::Neq { left, right } => self.eval_ne(*left, *right),
             ExprNode::Gt { left, right } => self.eval_gt(*left, *right),
             ExprNode::Lt { left, right } => self.eval_lt(*left, *right),
             ExprNode::Geq { left, right } => self.eval_geq(*left, *right),
             ExprNode::Leq { left, right } => self.eval_leq(*left, *right),
+            ExprNode::Cmp { left, right } => self.eval_cmp(*left, *right),
             ExprNode::Not(expr) => self.eval_not(*expr),
             ExprNode::Neg(expr) => self.eval_neg(*expr),
+            ExprNode::Len(expr) => self.eval_len(*expr),
+            ExprNode::BitNot(expr) => self.eval_bit_not(*expr),
+
+            // indexing
+            ExprNode::Index { base, index } => self.eval_index(*base, *index),
+            ExprNode::Slice { base, start, end } => {
+                self.eval_slice(*base, start.map(|e| *e), end.map(|e| *e))
+            }
 
             // arithmetic
             ExprNode::Add { left, right } => self.eval_add(*left, *right),
@@ -40,12 +51,23 @@ impl Runtime {
             ExprNode::Mul { left, right } => self.eval_mul(*left, *right),
             ExprNode::Div { left, right } => self.eval_div(*left, *right),
             ExprNode::Mod { left, right } => self.eval_mod(*left, *right),
+            ExprNode::Pow { left, right } => self.eval_pow(*left, *right),
+
+            // bitwise
+            ExprNode::BitAnd { left, right } => self.eval_bit_and(*left, *right),
+            ExprNode::BitOr { left, right } => self.eval_bit_or(*left, *right),
+            ExprNode::BitXor { left, right } => self.eval_bit_xor(*left, *right),
+            ExprNode::Shl { left, right } => self.eval_shl(*left, *right),
+            ExprNode::Shr { left, right } => self.eval_shr(*left, *right),
 
             ExprNode::Packed(expr) => self.eval(*expr),
+            ExprNode::Tuple(elems) => self.eval_tuple(elems),
+            ExprNode::List(elems) => self.eval_list(elems),
+            ExprNode::Interpolated(parts) => self.eval_interpolated(parts),
 
             // ExprNode::Call { name, args } => self.eval_call(name, args),
-            ExprNode::Call { name, args } => {
-                let return_val = self.eval_call(name, args)?;
+            ExprNode::Call { name, args, named } => {
+                let return_val = self.eval_call(name, args, named)?;
                 self.temp_ref(return_val);
 
                 Ok(return_val)
@@ -1 +1,223 @@
-use crate::runtime::runtime::runtime::Runtime;
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use parse::ast::expr::ExprNode;
+
+use crate::{
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{
+            objects::{Object, ObjectInner},
+            value::Value,
+        },
+        runtime::runtime::Runtime,
+    },
+};
+
+/// Most bits `**` and `<<` may give a result, beyond which they raise `Overflow`
+/// instead of taking up all time and memory
+pub(crate) const MAX_INT_BITS: u64 = 1 << 20;
+
+/// Operands of a numeric binary operator after promotion
+pub(crate) enum Numeric {
+    Int(i64, i64),
+    Big(BigInt, BigInt),
+    Float(f64, f64),
+}
+
+pub(crate) fn is_numeric(val: &Value) -> bool {
+    matches!(val, Value::Int(_) | Value::BigInt(_) | Value::Float(_))
+}
+
+/// int op int stays int, a big int makes both big,
+/// and any float makes both float
+pub(crate) fn promote(lval: Value, rval: Value) -> TwiResult<Numeric> {
+    let to_f64 = |b: &BigInt| b.to_f64().unwrap_or(f64::NAN);
+    match (lval, rval) {
+        (Value::Int(i1), Value::Int(i2)) => Ok(Numeric::Int(i1, i2)),
+        (Value::Int(i1), Value::BigInt(b2)) => Ok(Numeric::Big(i1.into(), b2)),
+        (Value::BigInt(b1), Value::Int(i2)) => Ok(Numeric::Big(b1, i2.into())),
+        (Value::BigInt(b1), Value::BigInt(b2)) => Ok(Numeric::Big(b1, b2)),
+        (Value::Int(i1), Value::Float(f2)) => Ok(Numeric::Float(i1 as f64, f2)),
+        (Value::Float(f1), Value::Int(i2)) => Ok(Numeric::Float(f1, i2 as f64)),
+        (Value::BigInt(b1), Value::Float(f2)) => Ok(Numeric::Float(to_f64(&b1), f2)),
+        (Value::Float(f1), Value::BigInt(b2)) => Ok(Numeric::Float(f1, to_f64(&b2))),
+        (Value::Float(f1), Value::Float(f2)) => Ok(Numeric::Float(f1, f2)),
+        (l, r) => Err(TwiError::IncompatibleBinopType {
+            left: l.to_string(),
+            right: r.to_string(),
+        }),
+    }
+}
+
+impl Runtime {
+    /// Integers are `Int` whenever they fit in 64 bits, and `BigInt` otherwise
+    pub(crate) fn alloc_int(&mut self, x: BigInt) -> Object {
+        match x.to_i64() {
+            Some(x) => self.alloc(ObjectInner::Int(x)),
+            None => self.alloc(ObjectInner::BigInt(x)),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn _operands(
+        &mut self,
+        left: ExprNode,
+        right: ExprNode,
+    ) -> TwiResult<(Value, Value)> {
+        let lobj = self.eval(left)?;
+        let robj = self.eval(right)?;
+
+        Ok((self.heap.get_value(lobj), self.heap.get_value(robj)))
+    }
+
+    /// `int_op` is checked, ints that overflow are promoted and retried with `big_op`
+    #[inline(always)]
+    fn _arith(
+        &mut self,
+        lval: Value,
+        rval: Value,
+        int_op: fn(i64, i64) -> Option<i64>,
+        big_op: fn(BigInt, BigInt) -> BigInt,
+        float_op: fn(f64, f64) -> f64,
+    ) -> TwiResult<Object> {
+        match promote(lval, rval)? {
+            Numeric::Int(i1, i2) => match int_op(i1, i2) {
+                Some(x) => Ok(self.alloc(ObjectInner::Int(x))),
+                None => Ok(self.alloc_int(big_op(i1.into(), i2.into()))),
+            },
+            Numeric::Big(b1, b2) => Ok(self.alloc_int(big_op(b1, b2))),
+            Numeric::Float(f1, f2) => Ok(self.alloc(ObjectInner::Float(float_op(f1, f2)))),
+        }
+    }
+
+    pub(crate) fn eval_neg(&mut self, expr: ExprNode) -> TwiResult<Object> {
+        let obj = self.eval(expr)?;
+        let val = self.heap.get_value(obj);
+
+        match val {
+            Value::Int(x) => match x.checked_neg() {
+                Some(x) => Ok(self.alloc(ObjectInner::Int(x))),
+                None => Ok(self.alloc_int(-BigInt::from(x))),
+            },
+            Value::BigInt(x) => Ok(self.alloc_int(-x)),
+            Value::Float(x) => Ok(self.alloc(ObjectInner::Float(-x))),
+            _ => Err(TwiError::IncompatibleUnopType(val.to_string())),
+        }
+    }
+
+    pub(crate) fn eval_add(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+
+        if let (Value::String(s1), Value::String(s2)) = (&lval, &rval) {
+            return Ok(self.alloc(ObjectInner::String(format!("{}{}", s1, s2))));
+        }
+        self._arith(
+            lval,
+            rval,
+            i64::checked_add,
+            |b1, b2| b1 + b2,
+            |f1, f2| f1 + f2,
+        )
+    }
+
+    pub(crate) fn eval_minus(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+        self._arith(
+            lval,
+            rval,
+            i64::checked_sub,
+            |b1, b2| b1 - b2,
+            |f1, f2| f1 - f2,
+        )
+    }
+
+    pub(crate) fn eval_mul(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+        self._arith(
+            lval,
+            rval,
+            i64::checked_mul,
+            |b1, b2| b1 * b2,
+            |f1, f2| f1 * f2,
+        )
+    }
+
+    pub(crate) fn eval_div(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+
+        if is_zero(&rval) {
+            return Err(TwiError::DivisionByZero);
+        }
+        self._arith(
+            lval,
+            rval,
+            i64::checked_div,
+            |b1, b2| b1 / b2,
+            |f1, f2| f1 / f2,
+        )
+    }
+
+    pub(crate) fn eval_mod(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+
+        if is_zero(&rval) {
+            return Err(TwiError::DivisionByZero);
+        }
+        self._arith(
+            lval,
+            rval,
+            i64::checked_rem,
+            |b1, b2| b1 % b2,
+            |f1, f2| f1 % f2,
+        )
+    }
+
+    pub(crate) fn eval_pow(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+        self.pow_values(lval, rval)
+    }
+
+    /// int ** non-negative int is an int, a negative exponent gives a float
+    pub(crate) fn pow_values(&mut self, lval: Value, rval: Value) -> TwiResult<Object> {
+        let (base, exp) = match promote(lval, rval)? {
+            Numeric::Int(base, exp) => (BigInt::from(base), BigInt::from(exp)),
+            Numeric::Big(base, exp) => (base, exp),
+            Numeric::Float(f1, f2) => return Ok(self.alloc(ObjectInner::Float(f1.powf(f2)))),
+        };
+
+        if exp < BigInt::zero() {
+            let base = base.to_f64().unwrap_or(f64::NAN);
+            let exp = exp.to_f64().unwrap_or(f64::NAN);
+            return Ok(self.alloc(ObjectInner::Float(base.powf(exp))));
+        }
+        // 0, 1 and -1 stay small, only the parity of the exponent matters
+        if base.bits() <= 1 {
+            let exp = match exp.is_zero() {
+                true => 0,
+                false => 2 - (&exp % 2u32).to_u32().unwrap_or_default(),
+            };
+            return Ok(self.alloc_int(base.pow(exp)));
+        }
+        // the result has at least this many bits, and less than twice as many
+        let bits = exp
+            .to_u64()
+            .and_then(|exp| exp.checked_mul(base.bits() - 1))
+            .map(|bits| bits + 1);
+        match bits {
+            Some(bits) if bits <= MAX_INT_BITS => {
+                let exp = exp.to_u32().expect("exponent is below MAX_INT_BITS");
+                Ok(self.alloc_int(base.pow(exp)))
+            }
+            _ => Err(TwiError::Overflow(format!("{} ** {}", base, exp))),
+        }
+    }
+}
+
+fn is_zero(val: &Value) -> bool {
+    match val {
+        Value::Int(x) => *x == 0,
+        Value::BigInt(x) => x.is_zero(),
+        Value::Float(x) => *x == 0.0,
+        _ => false,
+    }
+}
@@ -0,0 +1,118 @@
+use num_bigint::BigInt;
+use num_traits::Zero;
+use parse::ast::expr::ExprNode;
+
+use crate::{
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{
+            objects::{Object, ObjectInner},
+            value::Value,
+        },
+        runtime::{
+            eval::eval_arith::{promote, Numeric, MAX_INT_BITS},
+            runtime::Runtime,
+        },
+    },
+};
+
+/// Bitwise operators take ints and big ints, in two's complement
+fn integers(lval: Value, rval: Value) -> TwiResult<Numeric> {
+    match (&lval, &rval) {
+        (Value::Float(_), _) | (_, Value::Float(_)) => Err(TwiError::IncompatibleBinopType {
+            left: lval.to_string(),
+            right: rval.to_string(),
+        }),
+        _ => promote(lval, rval),
+    }
+}
+
+impl Runtime {
+    #[inline(always)]
+    fn _bitwise(
+        &mut self,
+        left: ExprNode,
+        right: ExprNode,
+        int_op: fn(i64, i64) -> i64,
+        big_op: fn(BigInt, BigInt) -> BigInt,
+    ) -> TwiResult<Object> {
+        let (lval, rval) = self._operands(left, right)?;
+
+        match integers(lval, rval)? {
+            Numeric::Int(i1, i2) => Ok(self.alloc(ObjectInner::Int(int_op(i1, i2)))),
+            Numeric::Big(b1, b2) => Ok(self.alloc_int(big_op(b1, b2))),
+            Numeric::Float(..) => unreachable!("floats are rejected by `integers`"),
+        }
+    }
+
+    /// The shifted value and the shift amount, which must be a non-negative int
+    #[inline(always)]
+    fn _shift_operands(
+        &mut self,
+        left: ExprNode,
+        right: ExprNode,
+        op: &str,
+    ) -> TwiResult<(BigInt, u32)> {
+        let (lval, rval) = self._operands(left, right)?;
+
+        let base = match lval {
+            Value::Int(x) => BigInt::from(x),
+            Value::BigInt(x) => x,
+            _ => {
+                return Err(TwiError::IncompatibleBinopType {
+                    left: lval.to_string(),
+                    right: rval.to_string(),
+                })
+            }
+        };
+        match rval {
+            Value::Int(n) if n < 0 => Err(TwiError::NegativeShift(n)),
+            Value::Int(n) => match u32::try_from(n) {
+                Ok(n) => Ok((base, n)),
+                Err(_) => Err(TwiError::Overflow(format!("{} {} {}", base, op, n))),
+            },
+            _ => Err(TwiError::IncompatibleBinopType {
+                left: base.to_string(),
+                right: rval.to_string(),
+            }),
+        }
+    }
+
+    pub(crate) fn eval_bit_and(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        self._bitwise(left, right, |i1, i2| i1 & i2, |b1, b2| b1 & b2)
+    }
+
+    pub(crate) fn eval_bit_or(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        self._bitwise(left, right, |i1, i2| i1 | i2, |b1, b2| b1 | b2)
+    }
+
+    pub(crate) fn eval_bit_xor(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        self._bitwise(left, right, |i1, i2| i1 ^ i2, |b1, b2| b1 ^ b2)
+    }
+
+    /// bits shifted out of an int promote it to a big int
+    pub(crate) fn eval_shl(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (base, n) = self._shift_operands(left, right, "<<")?;
+        if !base.is_zero() && base.bits() + u64::from(n) > MAX_INT_BITS {
+            return Err(TwiError::Overflow(format!("{} << {}", base, n)));
+        }
+        Ok(self.alloc_int(base << n))
+    }
+
+    /// arithmetic shift, rounding toward negative infinity
+    pub(crate) fn eval_shr(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let (base, n) = self._shift_operands(left, right, ">>")?;
+        Ok(self.alloc_int(base >> n))
+    }
+
+    pub(crate) fn eval_bit_not(&mut self, expr: ExprNode) -> TwiResult<Object> {
+        let obj = self.eval(expr)?;
+        let val = self.heap.get_value(obj);
+
+        match val {
+            Value::Int(x) => Ok(self.alloc(ObjectInner::Int(!x))),
+            Value::BigInt(x) => Ok(self.alloc_int(!x)),
+            _ => Err(TwiError::IncompatibleUnopType(val.to_string())),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use lex::token::teer;
 use parse::ast::expr::ExprNode;
 
 use crate::{
@@ -7,23 +8,26 @@ use crate::{
             objects::{Object, ObjectInner},
             value::Value,
         },
-        runtime::runtime::Runtime,
+        runtime::{
+            eval::eval_arith::{is_numeric, promote, Numeric},
+            runtime::Runtime,
+        },
     },
 };
 
 #[derive(Debug, Clone, Copy)]
-enum Ordering {
+pub(crate) enum Ordering {
     Less,
     Equal,
     Greater,
 }
 
 impl Ordering {
-    fn lt(&self) -> bool {
+    pub(crate) fn lt(&self) -> bool {
         matches!(self, Ordering::Less)
     }
 
-    fn gt(&self) -> bool {
+    pub(crate) fn gt(&self) -> bool {
         matches!(self, Ordering::Greater)
     }
 
@@ -34,6 +38,118 @@ impl Ordering {
     fn geq(&self) -> bool {
         matches!(self, Ordering::Greater | Ordering::Equal)
     }
+
+    fn eq(&self) -> bool {
+        matches!(self, Ordering::Equal)
+    }
+
+    fn teer(&self) -> teer {
+        match self {
+            Ordering::Less => teer::exile,
+            Ordering::Equal => teer::empty,
+            Ordering::Greater => teer::excel,
+        }
+    }
+}
+
+impl From<std::cmp::Ordering> for Ordering {
+    fn from(ord: std::cmp::Ordering) -> Self {
+        match ord {
+            std::cmp::Ordering::Less => Ordering::Less,
+            std::cmp::Ordering::Equal => Ordering::Equal,
+            std::cmp::Ordering::Greater => Ordering::Greater,
+        }
+    }
+}
+
+fn float_order(f1: f64, f2: f64) -> Ordering {
+    if f1 < f2 {
+        Ordering::Less
+    } else if f1 == f2 {
+        Ordering::Equal
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// ints and big ints compare exactly, anything with a float compares as floats
+fn numeric_order(lval: Value, rval: Value) -> TwiResult<Ordering> {
+    let ord = match promote(lval, rval)? {
+        Numeric::Int(i1, i2) => i1.cmp(&i2).into(),
+        Numeric::Big(b1, b2) => b1.cmp(&b2).into(),
+        Numeric::Float(f1, f2) => float_order(f1, f2),
+    };
+    Ok(ord)
+}
+
+// Kleene three-valued logic: excel is true, exile is false, empty is unknown
+fn teer_and(t1: teer, t2: teer) -> teer {
+    match (t1, t2) {
+        (teer::exile, _) | (_, teer::exile) => teer::exile,
+        (teer::excel, teer::excel) => teer::excel,
+        _ => teer::empty,
+    }
+}
+
+fn teer_or(t1: teer, t2: teer) -> teer {
+    match (t1, t2) {
+        (teer::excel, _) | (_, teer::excel) => teer::excel,
+        (teer::exile, teer::exile) => teer::exile,
+        _ => teer::empty,
+    }
+}
+
+fn teer_not(t: teer) -> teer {
+    match t {
+        teer::excel => teer::exile,
+        teer::empty => teer::empty,
+        teer::exile => teer::excel,
+    }
+}
+
+fn value_eq(lval: Value, rval: Value) -> TwiResult<bool> {
+    let eq = match (lval, rval) {
+        (Value::Func { hid: hid1, .. }, Value::Func { hid: hid2, .. }) => hid1 == hid2,
+        (Value::Native { hid: hid1, .. }, Value::Native { hid: hid2, .. }) => hid1 == hid2,
+        (Value::Model { hid: hid1, .. }, Value::Model { hid: hid2, .. }) => hid1 == hid2,
+        (Value::ModelRef { hid: hid1, .. }, Value::ModelRef { hid: hid2, .. }) => hid1 == hid2,
+        (Value::Nil, Value::Nil) => true,
+        (l, r) if is_numeric(&l) && is_numeric(&r) => numeric_order(l, r)?.eq(),
+        (Value::Teer(t1), Value::Teer(t2)) => t1 == t2,
+        (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
+        (Value::String(s1), Value::String(s2)) => s1 == s2,
+        (Value::Tuple(t1), Value::Tuple(t2)) | (Value::List(t1), Value::List(t2)) => {
+            if t1.len() != t2.len() {
+                return Ok(false);
+            }
+            for (v1, v2) in t1.into_iter().zip(t2) {
+                if !value_eq(v1, v2)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        (l, r) => {
+            return Err(TwiError::IncompatibleBinopType {
+                left: l.to_string(),
+                right: r.to_string(),
+            })
+        }
+    };
+
+    Ok(eq)
+}
+
+/// Numbers are ordered by value, strings lexicographically
+pub(crate) fn value_order(lval: Value, rval: Value) -> TwiResult<Ordering> {
+    match (lval, rval) {
+        (l, r) if is_numeric(&l) && is_numeric(&r) => numeric_order(l, r),
+        (Value::String(s1), Value::String(s2)) => Ok(s1.cmp(&s2).into()),
+        (l, r) => Err(TwiError::IncompatibleBinopType {
+            left: l.to_string(),
+            right: r.to_string(),
+        }),
+    }
 }
 
 impl Runtime {
@@ -45,24 +161,7 @@ impl Runtime {
         let lval = self.heap.get_value(lobj);
         let rval = self.heap.get_value(robj);
 
-        let eq = match (lval, rval) {
-            (Value::Func { hid: hid1, .. }, Value::Func { hid: hid2, .. }) => hid1 == hid2,
-            (Value::Model { hid: hid1, .. }, Value::Model { hid: hid2, .. }) => hid1 == hid2,
-            (Value::Nil, Value::Nil) => true,
-            (Value::Int(i1), Value::Int(i2)) => i1 == i2,
-            (Value::Float(f1), Value::Float(f2)) => f1 == f2,
-            (Value::Teer(t1), Value::Teer(t2)) => t1 == t2,
-            (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
-            (Value::String(s1), Value::String(s2)) => s1 == s2,
-            (l, r) => {
-                return Err(TwiError::IncompatibleBinopType {
-                    left: l.to_string(),
-                    right: r.to_string(),
-                })
-            }
-        };
-
-        Ok(eq)
+        value_eq(lval, rval)
     }
 
     #[inline(always)]
@@ -73,34 +172,49 @@ impl Runtime {
         let lval = self.heap.get_value(lobj);
         let rval = self.heap.get_value(robj);
 
-        let eq = match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                if i1 < i2 {
-                    Ordering::Less
-                } else if i1 == i2 {
-                    Ordering::Equal
-                } else {
-                    Ordering::Greater
-                }
-            }
-            (Value::Float(f1), Value::Float(f2)) => {
-                if f1 < f2 {
-                    Ordering::Less
-                } else if f1 == f2 {
-                    Ordering::Equal
+        value_order(lval, rval)
+    }
+
+    /// `&&` and `||` on two bools or two teers, short-circuiting
+    /// when the left operand decides the result
+    #[inline(always)]
+    fn _logic(&mut self, left: ExprNode, right: ExprNode, is_and: bool) -> TwiResult<Object> {
+        let lobj = self.eval(left)?;
+        let lval = self.heap.get_value(lobj);
+
+        match lval {
+            Value::Bool(b) if b != is_and => return Ok(lobj),
+            Value::Teer(teer::exile) if is_and => return Ok(lobj),
+            Value::Teer(teer::excel) if !is_and => return Ok(lobj),
+            _ => {}
+        }
+
+        let robj = self.eval(right)?;
+        let rval = self.heap.get_value(robj);
+
+        match (lval, rval) {
+            (Value::Bool(_), Value::Bool(_)) => Ok(robj),
+            (Value::Teer(t1), Value::Teer(t2)) => {
+                let t = if is_and {
+                    teer_and(t1, t2)
                 } else {
-                    Ordering::Greater
-                }
+                    teer_or(t1, t2)
+                };
+                Ok(self.alloc(ObjectInner::Teer(t)))
             }
-            (l, r) => {
-                return Err(TwiError::IncompatibleBinopType {
-                    left: l.to_string(),
-                    right: r.to_string(),
-                })
-            }
-        };
+            (l, r) => Err(TwiError::IncompatibleBinopType {
+                left: l.to_string(),
+                right: r.to_string(),
+            }),
+        }
+    }
 
-        Ok(eq)
+    pub(crate) fn eval_and(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        self._logic(left, right, true)
+    }
+
+    pub(crate) fn eval_or(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        self._logic(left, right, false)
     }
 
     pub(crate) fn eval_eq(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
@@ -133,133 +247,20 @@ impl Runtime {
         Ok(self.alloc(ObjectInner::Bool(ord.geq())))
     }
 
-    pub(crate) fn eval_not(&mut self, expr: ExprNode) -> TwiResult<Object> {
-        let obj = self.eval(expr)?;
-        let val = self.heap.get_value(obj);
-
-        if let Value::Bool(b) = val {
-            Ok(self.alloc(ObjectInner::Bool(!b)))
-        } else {
-            Err(TwiError::IncompatibleUnopType(val.to_string()))
-        }
+    /// `a <=> b` is excel, empty or exile when `a` is greater, equal or less
+    pub(crate) fn eval_cmp(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
+        let ord = self._order(left, right)?;
+        Ok(self.alloc(ObjectInner::Teer(ord.teer())))
     }
 
-    pub(crate) fn eval_neg(&mut self, expr: ExprNode) -> TwiResult<Object> {
+    pub(crate) fn eval_not(&mut self, expr: ExprNode) -> TwiResult<Object> {
         let obj = self.eval(expr)?;
         let val = self.heap.get_value(obj);
 
         match val {
-            Value::Int(x) => Ok(self.alloc(ObjectInner::Int(-x))),
-            Value::Float(x) => Ok(self.alloc(ObjectInner::Float(-x))),
+            Value::Bool(b) => Ok(self.alloc(ObjectInner::Bool(!b))),
+            Value::Teer(t) => Ok(self.alloc(ObjectInner::Teer(teer_not(t)))),
             _ => Err(TwiError::IncompatibleUnopType(val.to_string())),
         }
     }
-
-    pub(crate) fn eval_add(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
-        let lobj = self.eval(left)?;
-        let robj = self.eval(right)?;
-
-        let lval = self.heap.get_value(lobj);
-        let rval = self.heap.get_value(robj);
-
-        match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                //
-                Ok(self.alloc(ObjectInner::Int(i1 + i2)))
-            }
-            (Value::Float(f1), Value::Float(f2)) => Ok(self.alloc(ObjectInner::Float(f1 + f2))),
-            (l, r) => Err(TwiError::IncompatibleBinopType {
-                left: l.to_string(),
-                right: r.to_string(),
-            }),
-        }
-    }
-
-    pub(crate) fn eval_minus(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
-        let lobj = self.eval(left)?;
-        let robj = self.eval(right)?;
-
-        let lval = self.heap.get_value(lobj);
-        let rval = self.heap.get_value(robj);
-
-        match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                //
-                Ok(self.alloc(ObjectInner::Int(i1 - i2)))
-            }
-            (Value::Float(f1), Value::Float(f2)) => Ok(self.alloc(ObjectInner::Float(f1 - f2))),
-            (l, r) => Err(TwiError::IncompatibleBinopType {
-                left: l.to_string(),
-                right: r.to_string(),
-            }),
-        }
-    }
-
-    pub(crate) fn eval_mul(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
-        let lobj = self.eval(left)?;
-        let robj = self.eval(right)?;
-
-        let lval = self.heap.get_value(lobj);
-        let rval = self.heap.get_value(robj);
-
-        match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                //
-                Ok(self.alloc(ObjectInner::Int(i1 * i2)))
-            }
-            (Value::Float(f1), Value::Float(f2)) => Ok(self.alloc(ObjectInner::Float(f1 * f2))),
-            (l, r) => Err(TwiError::IncompatibleBinopType {
-                left: l.to_string(),
-                right: r.to_string(),
-            }),
-        }
-    }
-
-    pub(crate) fn eval_div(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
-        let lobj = self.eval(left)?;
-        let robj = self.eval(right)?;
-
-        let lval = self.heap.get_value(lobj);
-        let rval = self.heap.get_value(robj);
-
-        match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                if i2 == 0 {
-                    return Err(TwiError::DivisionByZero);
-                }
-                Ok(self.alloc(ObjectInner::Int(i1 / i2)))
-            }
-            (Value::Float(f1), Value::Float(f2)) => {
-                if f2 == 0.0 {
-                    return Err(TwiError::DivisionByZero);
-                }
-                Ok(self.alloc(ObjectInner::Float(f1 / f2)))
-            }
-            (l, r) => Err(TwiError::IncompatibleBinopType {
-                left: l.to_string(),
-                right: r.to_string(),
-            }),
-        }
-    }
-
-    pub(crate) fn eval_mod(&mut self, left: ExprNode, right: ExprNode) -> TwiResult<Object> {
-        let lobj = self.eval(left)?;
-        let robj = self.eval(right)?;
-
-        let lval = self.heap.get_value(lobj);
-        let rval = self.heap.get_value(robj);
-
-        match (lval, rval) {
-            (Value::Int(i1), Value::Int(i2)) => {
-                if i2 == 0 {
-                    return Err(TwiError::DivisionByZero);
-                }
-                Ok(self.alloc(ObjectInner::Int(i1 % i2)))
-            }
-            (l, r) => Err(TwiError::IncompatibleBinopType {
-                left: l.to_string(),
-                right: r.to_string(),
-            }),
-        }
-    }
 }
@@ -0,0 +1,179 @@
+use parse::ast::expr::ExprNode;
+
+use crate::{
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{
+            objects::{Object, ObjectInner},
+            value::Value,
+        },
+        runtime::runtime::Runtime,
+    },
+};
+
+/// `[[fill]align][width][.precision]` after the `:` of a `{:spec}` placeholder
+struct FormatSpec {
+    fill: char,
+    align: Option<char>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl FormatSpec {
+    fn parse(spec: &str) -> TwiResult<Self> {
+        let invalid = || TwiError::InvalidFormat(format!("invalid spec `{}`", spec));
+        let chars: Vec<char> = spec.chars().collect();
+        let is_align = |c: Option<&char>| matches!(c, Some('<' | '>' | '^'));
+
+        let mut fmt = FormatSpec {
+            fill: ' ',
+            align: None,
+            width: 0,
+            precision: None,
+        };
+        let mut i = 0;
+        if is_align(chars.get(1)) {
+            fmt.fill = chars[0];
+            fmt.align = Some(chars[1]);
+            i = 2;
+        } else if is_align(chars.first()) {
+            fmt.align = Some(chars[0]);
+            i = 1;
+        }
+
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            chars[start..*i].iter().collect::<String>()
+        };
+        let width = digits(&mut i);
+        if !width.is_empty() {
+            fmt.width = width.parse().map_err(|_| invalid())?;
+        }
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            let precision = digits(&mut i);
+            fmt.precision = Some(precision.parse().map_err(|_| invalid())?);
+        }
+        if i != chars.len() {
+            return Err(invalid());
+        }
+        Ok(fmt)
+    }
+
+    /// numbers are right aligned by default, everything else left aligned
+    fn apply(&self, val: Value, text: String) -> String {
+        let text = match (&val, self.precision) {
+            (Value::Float(f), Some(p)) => format!("{:.*}", p, f),
+            (Value::String(_), Some(p)) => text.chars().take(p).collect(),
+            _ => text,
+        };
+
+        let len = text.chars().count();
+        if len >= self.width {
+            return text;
+        }
+        let pad = self.width - len;
+        let default_align = match val {
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) => '>',
+            _ => '<',
+        };
+        let fill = |n: usize| self.fill.to_string().repeat(n);
+        match self.align.unwrap_or(default_align) {
+            '>' => fill(pad) + &text,
+            '^' => fill(pad / 2) + &text + &fill(pad - pad / 2),
+            _ => text + &fill(pad),
+        }
+    }
+}
+
+impl Runtime {
+    /// Text of a value as printed or interpolated: strings are not quoted
+    pub(crate) fn to_text(&self, obj: Object) -> String {
+        match self.heap.get_value(obj) {
+            Value::String(x) => x,
+            _ => self.display(obj),
+        }
+    }
+
+    pub(crate) fn eval_interpolated(&mut self, parts: Vec<ExprNode>) -> TwiResult<Object> {
+        let mut text = String::new();
+        for part in parts {
+            let obj = self.eval(part)?;
+            text.push_str(&self.to_text(obj));
+        }
+        Ok(self.alloc(ObjectInner::String(text)))
+    }
+
+    /// `format(fmt, args...)`: each `{}` or `{:spec}` in `fmt` is replaced by the next arg
+    /// and `{{`, `}}` are literal braces, a raw `r"..."` fmt keeps them from interpolation
+    pub(crate) fn format_values(&mut self, fmt: Object, args: &[Object]) -> TwiResult<Object> {
+        let fmt = match self.heap.get_value(fmt) {
+            Value::String(s) => s,
+            val => {
+                return Err(TwiError::UnexpectedType {
+                    expected: "String".into(),
+                    got: val.to_string(),
+                })
+            }
+        };
+
+        let mut out = String::new();
+        let n_args = args.len();
+        let mut args = args.iter();
+        let mut n_placeholders = 0;
+        let mut chars = fmt.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    out.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    out.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(TwiError::InvalidFormat("unclosed `{`".to_string()))
+                            }
+                        }
+                    }
+                    let spec = match placeholder.strip_prefix(':') {
+                        Some(spec) => FormatSpec::parse(spec)?,
+                        None if placeholder.is_empty() => FormatSpec::parse("")?,
+                        None => {
+                            return Err(TwiError::InvalidFormat(format!(
+                                "invalid placeholder `{{{}}}`",
+                                placeholder
+                            )))
+                        }
+                    };
+
+                    n_placeholders += 1;
+                    if let Some(&arg) = args.next() {
+                        let text = self.to_text(arg);
+                        out.push_str(&spec.apply(self.heap.get_value(arg), text));
+                    }
+                }
+                '}' => return Err(TwiError::InvalidFormat("unmatched `}`".to_string())),
+                c => out.push(c),
+            }
+        }
+
+        if n_placeholders != n_args {
+            return Err(TwiError::FormatArgNumMismatch {
+                expected: n_placeholders,
+                got: n_args,
+            });
+        }
+        Ok(self.alloc(ObjectInner::String(out)))
+    }
+}
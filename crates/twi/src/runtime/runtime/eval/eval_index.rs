@@ -0,0 +1,109 @@
+use parse::ast::expr::ExprNode;
+
+use crate::{
+    errors::{TwiError, TwiResult},
+    runtime::{
+        gc::{
+            objects::{Object, ObjectInner},
+            value::Value,
+        },
+        runtime::runtime::Runtime,
+    },
+};
+
+impl Runtime {
+    /// `#x`: number of characters of a string or elements of a tuple or list
+    pub(crate) fn eval_len(&mut self, expr: ExprNode) -> TwiResult<Object> {
+        let obj = self.eval(expr)?;
+        self.len_of(obj)
+    }
+
+    pub(crate) fn len_of(&mut self, obj: Object) -> TwiResult<Object> {
+        let len = match self.heap.get_value(obj) {
+            Value::String(s) => s.chars().count(),
+            Value::Tuple(elems) | Value::List(elems) => elems.len(),
+            val => return Err(TwiError::IncompatibleUnopType(val.to_string())),
+        };
+        Ok(self.alloc(ObjectInner::Int(len as i64)))
+    }
+
+    /// `x[i]`: the i-th character of a string, or element of a tuple or list
+    pub(crate) fn eval_index(&mut self, base: ExprNode, index: ExprNode) -> TwiResult<Object> {
+        let base = self.eval(base)?;
+        let index = self.eval_position(index)?;
+
+        if let Value::String(s) = self.heap.get_value(base) {
+            return match s.chars().nth(index) {
+                Some(c) => Ok(self.alloc(ObjectInner::String(c.to_string()))),
+                None => Err(TwiError::IndexOutOfBounds {
+                    index,
+                    len: s.chars().count(),
+                }),
+            };
+        }
+
+        let elems = self.heap.elements(base)?;
+        match elems.get(index) {
+            Some(&elem) => Ok(elem),
+            None => Err(TwiError::IndexOutOfBounds {
+                index,
+                len: elems.len(),
+            }),
+        }
+    }
+
+    /// `x[a:b]`: characters or elements from `a` up to but excluding `b`,
+    /// the bounds default to the start and the end
+    pub(crate) fn eval_slice(
+        &mut self,
+        base: ExprNode,
+        start: Option<ExprNode>,
+        end: Option<ExprNode>,
+    ) -> TwiResult<Object> {
+        let base = self.eval(base)?;
+        let start = match start {
+            Some(start) => Some(self.eval_position(start)?),
+            None => None,
+        };
+        let end = match end {
+            Some(end) => Some(self.eval_position(end)?),
+            None => None,
+        };
+
+        if let Value::String(s) = self.heap.get_value(base) {
+            let chars: Vec<char> = s.chars().collect();
+            let (start, end) = slice_bounds(start, end, chars.len())?;
+            let sliced = chars[start..end].iter().collect();
+            return Ok(self.alloc(ObjectInner::String(sliced)));
+        }
+
+        let elems = self.heap.elements(base)?;
+        let (start, end) = slice_bounds(start, end, elems.len())?;
+        let sliced = elems[start..end].to_vec();
+        match self.heap.get_value(base) {
+            Value::Tuple(_) => Ok(self.alloc(ObjectInner::Tuple(sliced))),
+            _ => Ok(self.alloc(ObjectInner::List(sliced))),
+        }
+    }
+
+    /// An index or slice bound, which must be a non-negative int
+    fn eval_position(&mut self, expr: ExprNode) -> TwiResult<usize> {
+        let obj = self.eval(expr)?;
+        match self.heap.get_value(obj) {
+            Value::Int(i) if i >= 0 => Ok(i as usize),
+            val => Err(TwiError::UnexpectedType {
+                expected: "non-negative Int".into(),
+                got: val.to_string(),
+            }),
+        }
+    }
+}
+
+fn slice_bounds(start: Option<usize>, end: Option<usize>, len: usize) -> TwiResult<(usize, usize)> {
+    let start = start.unwrap_or(0);
+    let end = end.unwrap_or(len);
+    if start > end || end > len {
+        return Err(TwiError::SliceOutOfBounds { start, end, len });
+    }
+    Ok((start, end))
+}
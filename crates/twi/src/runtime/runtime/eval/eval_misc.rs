@@ -2,17 +2,21 @@ use parse::{ast::expr::ExprNode, types::literal::Literal};
 use std::collections::BTreeMap;
 
 use crate::{
-    errors::{TwiError, TwiResult},
+    errors::{arg_count, TwiError, TwiResult},
     runtime::{
         gc::{
             objects::{Object, ObjectInner},
             value::Value,
         },
-        runtime::runtime::Runtime,
+        runtime::runtime::{Frame, Runtime},
     },
     scope::scope::ScopeType,
 };
 
+/// Stack space left below which a call continues on a new segment
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
 impl Runtime {
     pub fn eval_literal(&mut self, lit: Literal) -> Object {
         self.alloc(lit.into())
@@ -40,60 +44,195 @@ impl Runtime {
         }
     }
 
-    pub fn eval_call(&mut self, funcname: String, args: Vec<ExprNode>) -> TwiResult<Object> {
+    pub fn eval_tuple(&mut self, elems: Vec<ExprNode>) -> TwiResult<Object> {
+        let mut objs = Vec::new();
+        for elem in elems {
+            objs.push(self.eval(elem)?);
+        }
+        Ok(self.alloc(ObjectInner::Tuple(objs)))
+    }
+
+    /// Elements of a tuple with exactly `n` elements
+    pub fn unpack(&self, obj: Object, n: usize) -> TwiResult<Vec<Object>> {
+        let elems = self.heap.elements(obj)?;
+        if elems.len() != n {
+            return Err(TwiError::TupleArityMismatch {
+                expected: n,
+                got: elems.len(),
+            });
+        }
+        Ok(elems)
+    }
+
+    pub fn eval_list(&mut self, elems: Vec<ExprNode>) -> TwiResult<Object> {
+        let mut objs = Vec::new();
+        for elem in elems {
+            objs.push(self.eval(elem)?);
+        }
+        Ok(self.alloc(ObjectInner::List(objs)))
+    }
+
+    pub fn eval_call(
+        &mut self,
+        funcname: String,
+        args: Vec<ExprNode>,
+        named: Vec<(String, ExprNode)>,
+    ) -> TwiResult<Object> {
         let callable = self.getvar(funcname.clone())?;
+        let call = ExprNode::call(funcname.clone(), args.clone(), named.clone());
+
+        // Do not use iterator/adapter here
+        // since we need to do error prop
+        let mut args_val = Vec::new();
+        for arg in args {
+            args_val.push(self.eval(arg)?);
+        }
+        let mut named_val = Vec::new();
+        for (name, arg) in named {
+            named_val.push((name, self.eval(arg)?));
+        }
+        self.call_object(funcname, Some(call), callable, args_val, named_val)
+    }
+
+    /// Call a func or native with evaluated args, `funcname` is the name it is called by
+    /// and `call` the call expression, if it is called from SL
+    pub(crate) fn call_object(
+        &mut self,
+        funcname: String,
+        call: Option<ExprNode>,
+        callable: Object,
+        args_val: Vec<Object>,
+        named: Vec<(String, Object)>,
+    ) -> TwiResult<Object> {
+        // the outermost frame is not a call
+        if self.frames.len() > self.max_call_depth {
+            let res = Err(TwiError::StackOverflow {
+                funcname,
+                depth: self.max_call_depth,
+            });
+            self.record_backtrace(&res);
+            return res;
+        }
+
+        self.frames.push(Frame {
+            funcname: funcname.clone(),
+            call,
+        });
+        // SL calls recurse in Rust, deep ones continue on stack segments from the heap
+        let res = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.call_in_frame(funcname, callable, args_val, named)
+        });
+        self.record_backtrace(&res);
+        self.frames.pop();
+        res
+    }
+
+    fn call_in_frame(
+        &mut self,
+        funcname: String,
+        callable: Object,
+        mut args_val: Vec<Object>,
+        named: Vec<(String, Object)>,
+    ) -> TwiResult<Object> {
         let func = self.heap.get_value(callable);
+        if let Value::Native { native, .. } = func {
+            return self.call_native(native, args_val, named);
+        }
         if let Value::Func {
-            mut params,
+            params,
             hid: _,
             body,
         } = func
         {
-            if params.len() != args.len() {
+            let variadic = params.last().is_some_and(|param| param.variadic);
+            let n_fixed = if variadic {
+                params.len() - 1
+            } else {
+                params.len()
+            };
+            if args_val.len() > n_fixed && !variadic {
+                let n_required = params
+                    .iter()
+                    .filter(|param| param.default.is_none())
+                    .count();
                 return Err(TwiError::ArgNumMismatch {
                     funcname: funcname.clone(),
-                    expected: params.len(),
-                    got: args.len(),
+                    expected: arg_count(n_required, Some(n_fixed)),
+                    got: args_val.len(),
                 });
             }
 
-            // Do not use iterator/adapter here
-            // since we need to do error prop
-            let mut args_val = Vec::new();
-            // push arguments
-            for arg in args {
-                args_val.push(self.eval(arg)?);
+            let mut named_val = Vec::new();
+            for (name, arg) in named {
+                let index = params
+                    .iter()
+                    .position(|param| param.name == name && !param.variadic);
+                match index {
+                    None => {
+                        return Err(TwiError::UnknownNamedArg {
+                            funcname: funcname.clone(),
+                            name,
+                        })
+                    }
+                    Some(i) if i < args_val.len() => {
+                        return Err(TwiError::DuplicateArgBinding {
+                            funcname: funcname.clone(),
+                            param: name,
+                        })
+                    }
+                    Some(_) => named_val.push((name, arg)),
+                }
             }
+            // extra positional args are collected by the variadic param
+            let rest = if variadic {
+                let extra = args_val.split_off(n_fixed.min(args_val.len()));
+                Some(self.alloc(ObjectInner::List(extra)))
+            } else {
+                None
+            };
             // push this function itself
-            // first push arg, since push param will modify param list
-            // arg
             let thisfunc = self.alloc(ObjectInner::Func {
                 params: params.clone(),
                 body: body.clone(),
             });
-            args_val.push(thisfunc);
-            // param
-            params.push(funcname);
 
             // return nil by default
             {
                 // start a call
                 let sg = self.enter_scope(ScopeType::Call);
                 // push all arguments
-                for (name, val) in params.into_iter().zip(args_val) {
-                    self.cur_scope_mut().add(name, val);
+                let mut positional = args_val.into_iter();
+                for param in &params[..n_fixed] {
+                    let val = if let Some(val) = positional.next() {
+                        val
+                    } else if let Some(i) =
+                        named_val.iter().position(|(name, _)| *name == param.name)
+                    {
+                        named_val.swap_remove(i).1
+                    } else if let Some(default) = &param.default {
+                        // defaults are evaluated in the call scope, they can refer to earlier params
+                        self.eval(default.clone())?
+                    } else {
+                        return Err(TwiError::MissingArg {
+                            funcname: funcname.clone(),
+                            param: param.name.clone(),
+                        });
+                    };
+                    self.cur_scope_mut().add(param.name.clone(), val);
                 }
+                if let Some(rest) = rest {
+                    self.cur_scope_mut().add(params[n_fixed].name.clone(), rest);
+                }
+                self.cur_scope_mut().add(funcname, thisfunc);
                 // execute call body
-                for stmt in body {
-                    let res = self.exec_stmt(stmt);
-                    match res {
-                        Ok(_) => {}
-                        Err(TwiError::Return(val)) => {
-                            // exit scope
-                            return Ok(val);
-                        }
-                        Err(e) => return Err(e),
+                let res = self.exec_block(body);
+                match sg.exit(res) {
+                    Ok(_) => {}
+                    Err(TwiError::Return(val)) => {
+                        // exit scope
+                        return Ok(val);
                     }
+                    Err(e) => return Err(e),
                 }
                 // exit scope
             };
@@ -134,6 +273,9 @@ impl Runtime {
         let (local, global) = self.scopes_mut();
         let is_call = local.iter().map(|s| s.is_call()).collect::<Vec<_>>();
         'find_local: for (scope, is_call) in local.iter_mut().zip(is_call.into_iter()).rev() {
+            if scope.consts.contains(&ident) {
+                return Err(TwiError::AssignToConst(ident));
+            }
             if let Some(obj) = scope.vars.get_mut(&ident) {
                 return Ok(obj);
             }
@@ -142,6 +284,9 @@ impl Runtime {
             }
         }
         // global
+        if global.consts.contains(&ident) {
+            return Err(TwiError::AssignToConst(ident));
+        }
         if let Some(obj) = global.vars.get_mut(&ident) {
             Ok(obj)
         } else {
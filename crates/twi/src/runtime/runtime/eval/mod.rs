@@ -1,5 +1,8 @@
 pub mod conversion;
 pub mod eval;
 pub mod eval_arith;
+pub mod eval_bitwise;
 pub mod eval_bool;
+pub mod eval_format;
+pub mod eval_index;
 pub mod eval_misc;
@@ -1,4 +1,7 @@
 pub mod runtime;
+pub mod runtime_check;
 pub mod runtime_exec;
+pub mod runtime_display;
 pub mod runtime_impl;
+pub mod stdlib;
 pub mod eval;
\ No newline at end of file
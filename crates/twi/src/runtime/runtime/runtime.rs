@@ -1,10 +1,10 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
+    io::{BufRead, Write},
     iter,
 };
 
-use colored::Colorize;
-use log::warn;
+use log::debug;
 use parse::ast::{
     expr::ExprNode,
     root::{self, Root},
@@ -12,11 +12,14 @@ use parse::ast::{
 };
 
 use crate::{
-    errors::TwiResult,
+    capabilities::Capabilities,
+    errors::{TwiError, TwiResult},
     runtime::gc::{
         gc::Heap,
         objects::{Object, ObjectInner},
+        persistent::Persistent,
         timer::Timer,
+        value::Value,
     },
     scope::{
         scope::{Scope, ScopeType},
@@ -36,6 +39,32 @@ pub struct GlobalVar {
     pub(crate) obj: Object,
 }
 
+/// A function being called, innermost last in `Runtime::frames`.
+/// The outermost frame is `main`, or `<script>` for top-level statements.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub(crate) funcname: String,
+    /// the call as written by the caller, none for the outermost frame and host calls
+    pub(crate) call: Option<ExprNode>,
+}
+
+impl Frame {
+    pub(crate) fn outermost(funcname: String) -> Self {
+        Self {
+            funcname,
+            call: None,
+        }
+    }
+
+    /// The call when it is known, as in `fact(n - 1)`, or else the function name
+    fn describe(&self) -> String {
+        match &self.call {
+            Some(call) => call.to_string(),
+            None => self.funcname.clone(),
+        }
+    }
+}
+
 // ########################################################
 // ####################### Runtime ########################
 // ########################################################
@@ -44,27 +73,124 @@ pub struct Runtime {
     pub(crate) models: BTreeMap<String, Model>,
 
     pub(crate) program: Vec<StmtNode>,
+    /// global `let`s and `const`s before `main`, evaluated when the program starts running
+    pub(crate) globals: Vec<StmtNode>,
 
     pub(crate) heap: Heap,
 
     // runtime data
     pub(crate) scopes: Vec<Scope>,
     pub(crate) global_scope: Scope,
+    /// calls in progress, alongside their call scopes
+    pub(crate) frames: Vec<Frame>,
+    /// frames at the point the last uncaught error was raised
+    pub(crate) backtrace: Option<Vec<Frame>>,
+    /// how many calls may be in progress at once
+    pub(crate) max_call_depth: usize,
 
     pub(crate) gc_interval: f64,
     pub(crate) gc_timer: Timer,
+
+    /// how deep nested values are displayed
+    pub(crate) display_depth: usize,
+
+    /// where `print` writes, stdout by default
+    pub(crate) output: Box<dyn Write>,
+    /// print without the `[SL print]` prefix
+    pub(crate) plain: bool,
+    /// where `read_line` reads, stdin by default
+    pub(crate) input: Box<dyn BufRead>,
+    /// what I/O builtins may access
+    pub(crate) caps: Capabilities,
+    /// name of the param of `main(args)`
+    pub(crate) main_param: Option<String>,
 }
 
 impl Runtime {
-    pub fn try_new(root: Root, gc_interval: f64) -> TwiResult<Self> {
-        let mut rt = Self::structure(root.statements)?;
-        dbg!(&rt.global_scope.vars);
+    pub fn try_new(root: Root, gc_interval: f64, script: bool) -> TwiResult<Self> {
+        let mut rt = Self::structure(root.statements, script)?;
         rt.gc_interval = gc_interval;
-        rt.scopes.push(Scope::call());
         Ok(rt)
     }
 }
 
+// io methods
+impl Runtime {
+    /// Send the program's output to `output` instead of stdout
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.output = Box::new(output);
+    }
+
+    /// Read `read_line` input from `input` instead of stdin
+    pub fn set_input(&mut self, input: impl BufRead + 'static) {
+        self.input = Box::new(input);
+    }
+
+    /// Raise a `StackOverflow` error when a call would make more than `depth` calls in progress
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Allow the I/O builtins what `caps` allows, they are all denied by default
+    pub fn set_capabilities(&mut self, caps: Capabilities) {
+        self.caps = caps;
+    }
+
+    /// Print program output as is, without the `[SL print]` prefix
+    pub fn set_plain(&mut self, plain: bool) {
+        self.plain = plain;
+    }
+
+    pub(crate) fn write_line(&mut self, line: &str) -> TwiResult<()> {
+        let res = if self.plain {
+            writeln!(self.output, "{}", line)
+        } else {
+            writeln!(self.output, "[SL print] {}", line)
+        };
+        res.map_err(|e| TwiError::Io(e.to_string()))
+    }
+}
+
+// backtrace methods
+impl Runtime {
+    /// Remember the frames when `res` is an error that is not recorded yet,
+    /// called before the frame raising it is popped
+    pub(crate) fn record_backtrace<T>(&mut self, res: &TwiResult<T>) {
+        let is_error = matches!(res, Err(e) if !matches!(e, TwiError::Return(_)));
+        if is_error && self.backtrace.is_none() {
+            self.backtrace = Some(self.frames.clone());
+        }
+    }
+
+    /// Where the last uncaught error was raised, innermost call first, each call as written.
+    /// Runs of calls to the same function, as in deep recursion, are shown once.
+    pub(crate) fn backtrace_lines(&self) -> Vec<String> {
+        let frames = self.backtrace.as_deref().unwrap_or_default();
+        let mut lines = Vec::new();
+        let mut frames = frames.iter().rev().peekable();
+        while let Some(frame) = frames.next() {
+            lines.push(match lines.is_empty() {
+                true => format!("in {}", frame.describe()),
+                false => format!("called from {}", frame.describe()),
+            });
+            let mut repeated = 0;
+            while frames
+                .next_if(|next| next.funcname == frame.funcname)
+                .is_some()
+            {
+                repeated += 1;
+            }
+            if repeated > 0 {
+                lines.push(format!(
+                    "called from {} {} more times",
+                    frame.funcname, repeated
+                ));
+            }
+        }
+        lines
+    }
+}
+
 // memory methods
 impl Runtime {
     pub(crate) fn alloc(&mut self, obj_inner: ObjectInner) -> Object {
@@ -73,14 +199,70 @@ impl Runtime {
         obj
     }
 
+    /// Snapshot of an object's value
+    pub fn value(&self, obj: Object) -> Value {
+        self.heap.get_value(obj)
+    }
+
+    /// Keep `obj` alive across collections while the handle exists
+    pub fn persist(&self, obj: Object) -> Persistent {
+        self.heap.persist(obj)
+    }
+
+    /// Allocate a value, alive until the current scope exits.
+    /// Tuples and lists are allocated element by element, while functions and
+    /// models refer to the objects they were read from, which must still be alive.
+    pub fn alloc_value(&mut self, val: Value) -> Object {
+        let obj_inner = match val {
+            Value::Nil => ObjectInner::Nil,
+            Value::Int(x) => ObjectInner::Int(x),
+            Value::BigInt(x) => return self.alloc_int(x),
+            Value::Float(x) => ObjectInner::Float(x),
+            Value::Teer(x) => ObjectInner::Teer(x),
+            Value::Bool(x) => ObjectInner::Bool(x),
+            Value::String(x) => ObjectInner::String(x),
+            Value::Tuple(elems) => {
+                let elems = elems
+                    .into_iter()
+                    .map(|elem| self.alloc_value(elem))
+                    .collect();
+                ObjectInner::Tuple(elems)
+            }
+            Value::List(elems) => {
+                let elems = elems
+                    .into_iter()
+                    .map(|elem| self.alloc_value(elem))
+                    .collect();
+                ObjectInner::List(elems)
+            }
+            Value::Func { hid, .. }
+            | Value::Native { hid, .. }
+            | Value::Model { hid, .. }
+            | Value::ModelRef { hid, .. } => {
+                let obj = Object { hid };
+                self.temp_ref(obj);
+                return obj;
+            }
+        };
+        self.alloc(obj_inner)
+    }
+
     // reference temporarily and annonymously
     pub(crate) fn temp_ref(&mut self, obj: Object) {
         self.cur_scope_mut().unnamed.push(obj);
     }
 
+    /// Root a returned or thrown value in the current scope, while more statements
+    /// run before it reaches its destination
+    pub(crate) fn root_pending<T>(&mut self, res: &TwiResult<T>) {
+        if let Err(TwiError::Return(obj) | TwiError::Throw(obj)) = res {
+            self.temp_ref(*obj);
+        }
+    }
+
     pub(crate) fn bind(&mut self, name: String, obj_inner: ObjectInner) -> Object {
         let obj = self.heap.alloc(obj_inner);
-        self.cur_scope_mut().vars.insert(name, obj);
+        self.cur_scope_mut().add(name, obj);
         obj
     }
 
@@ -95,13 +277,9 @@ impl Runtime {
                 roots.insert(obj);
             }
         }
-        println!(
-            "{}",
-            format!(
-                "[SL GC] Collecting unused objects, roots.len={}",
-                roots.len()
-            )
-            .color("#888888")
+        debug!(
+            "[SL GC] Collecting unused objects, roots.len={}",
+            roots.len()
         );
         self.heap.gc(roots.into_iter().collect());
     }
@@ -131,7 +309,10 @@ impl Runtime {
         self.scopes.push(Scope {
             scope_type: type_,
             vars: BTreeMap::new(),
+            consts: BTreeSet::new(),
+            builtins: BTreeSet::new(),
             unnamed: Vec::new(),
+            deferred: Vec::new(),
         });
 
         ScopeGuard {
@@ -139,6 +320,21 @@ impl Runtime {
         }
     }
 
+    /// Run the current scope's deferred statements, last registered first,
+    /// before leaving it with `res`; an error raised by one of them replaces `res`
+    pub(crate) fn run_deferred<T>(&mut self, res: TwiResult<T>) -> TwiResult<T> {
+        // keep a returned or thrown value alive while deferred statements run
+        self.root_pending(&res);
+
+        let mut res = res;
+        while let Some(stmt) = self.cur_scope_mut().deferred.pop() {
+            if let Err(e) = self.exec_stmt(stmt) {
+                res = Err(e);
+            }
+        }
+        res
+    }
+
     pub(crate) fn global(&self) -> &Scope {
         &self.global_scope
     }
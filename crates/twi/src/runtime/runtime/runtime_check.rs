@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use parse::ast::stmt::{Lvalue, Pattern, StmtNode};
+
+use crate::errors::{TwiError, TwiResult};
+
+/// Names bound in one lexical scope, mapped to whether they are `const`
+struct Frame {
+    is_call: bool,
+    names: BTreeMap<String, bool>,
+}
+
+/// Mirrors the runtime's name resolution (blocks up to the nearest call scope, then globals)
+/// to reject reassignment of `const` bindings before the program runs.
+struct ConstChecker {
+    globals: BTreeMap<String, bool>,
+    frames: Vec<Frame>,
+}
+
+/// Static check: no `const` binding is the target of an assignment
+pub fn check_consts(statements: &[StmtNode]) -> TwiResult<()> {
+    let mut checker = ConstChecker {
+        globals: BTreeMap::new(),
+        frames: Vec::new(),
+    };
+
+    // globals are visible to every function body, wherever they are defined
+    for stmt in statements {
+        match stmt {
+            StmtNode::Let { ident, .. } => checker.declare(ident, false),
+            StmtNode::Const { ident, .. } => checker.declare(ident, true),
+            StmtNode::LetTuple { idents, .. } => {
+                for ident in idents {
+                    checker.declare(ident, false);
+                }
+            }
+            StmtNode::FuncDef { name, .. } => checker.declare(name, false),
+            _ => {}
+        }
+    }
+
+    checker.check_body(statements)
+}
+
+impl ConstChecker {
+    fn declare(&mut self, ident: &str, constant: bool) {
+        let names = match self.frames.last_mut() {
+            Some(frame) => &mut frame.names,
+            None => &mut self.globals,
+        };
+        names.insert(ident.to_string(), constant);
+    }
+
+    fn is_const(&self, ident: &str) -> bool {
+        for frame in self.frames.iter().rev() {
+            if let Some(&constant) = frame.names.get(ident) {
+                return constant;
+            }
+            if frame.is_call {
+                break;
+            }
+        }
+        self.globals.get(ident).copied().unwrap_or(false)
+    }
+
+    fn check_scoped(
+        &mut self,
+        is_call: bool,
+        names: Vec<String>,
+        body: &[StmtNode],
+    ) -> TwiResult<()> {
+        self.frames.push(Frame {
+            is_call,
+            names: names.into_iter().map(|name| (name, false)).collect(),
+        });
+        let res = self.check_body(body);
+        self.frames.pop();
+        res
+    }
+
+    fn check_body(&mut self, body: &[StmtNode]) -> TwiResult<()> {
+        for stmt in body {
+            self.check_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn check_stmt(&mut self, stmt: &StmtNode) -> TwiResult<()> {
+        match stmt {
+            StmtNode::Let { ident, .. } => self.declare(ident, false),
+            StmtNode::Const { ident, .. } => self.declare(ident, true),
+            StmtNode::LetTuple { idents, .. } => {
+                for ident in idents {
+                    self.declare(ident, false);
+                }
+            }
+            StmtNode::Assign {
+                target: Lvalue::Identifier(ident),
+                ..
+            } if self.is_const(ident) => {
+                return Err(TwiError::AssignToConst(ident.clone()));
+            }
+            StmtNode::AssignTuple { targets, .. } => {
+                for target in targets {
+                    if let Lvalue::Identifier(ident) = target {
+                        if self.is_const(ident) {
+                            return Err(TwiError::AssignToConst(ident.clone()));
+                        }
+                    }
+                }
+            }
+            StmtNode::FuncDef { name, params, body } => {
+                self.declare(name, false);
+                // the function is bound to its own name inside the call
+                let mut names: Vec<_> = params.iter().map(|param| param.name.clone()).collect();
+                names.push(name.clone());
+                self.check_scoped(true, names, body)?;
+            }
+            StmtNode::For { iter, body, .. } => {
+                self.check_scoped(false, vec![iter.clone()], body)?;
+            }
+            StmtNode::While { body, .. } | StmtNode::If { body, .. } | StmtNode::Scope { body } => {
+                self.check_scoped(false, Vec::new(), body)?;
+            }
+            StmtNode::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                self.check_scoped(false, Vec::new(), body)?;
+                if let Some((ident, catch_body)) = catch {
+                    self.check_scoped(false, vec![ident.clone()], catch_body)?;
+                }
+                if let Some(finally_body) = finally {
+                    self.check_scoped(false, Vec::new(), finally_body)?;
+                }
+            }
+            StmtNode::Match { arms, .. } => {
+                for (pattern, body) in arms {
+                    let names = match pattern {
+                        Pattern::Model { bindings, .. } => {
+                            bindings.iter().map(|(_, ident)| ident.clone()).collect()
+                        }
+                        _ => Vec::new(),
+                    };
+                    self.check_scoped(false, names, body)?;
+                }
+            }
+            // runs in the scope it is registered in
+            StmtNode::Defer { stmt } => self.check_stmt(stmt)?,
+            StmtNode::IfElse {
+                if_body, else_body, ..
+            } => {
+                self.check_scoped(false, Vec::new(), if_body)?;
+                self.check_scoped(false, Vec::new(), else_body)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
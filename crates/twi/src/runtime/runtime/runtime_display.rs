@@ -0,0 +1,68 @@
+use crate::runtime::gc::{
+    objects::{Object, ObjectInner},
+    value::{fmt_elems, fmt_model, fmt_params},
+};
+
+use super::runtime::Runtime;
+
+impl Runtime {
+    /// SL syntax of an object, following references through the heap.
+    /// Model fields are shown in declaration order, a model containing itself
+    /// is shown as `<cycle Person#hid>`, and containers nested deeper than
+    /// `display_depth` are elided as `Person { .. }`, `(..)` or `[..]`.
+    pub(crate) fn display(&self, obj: Object) -> String {
+        self.display_nested(obj, 0, &mut Vec::new())
+    }
+
+    /// `path` holds the containers being displayed, from the outermost one
+    fn display_nested(&self, obj: Object, depth: usize, path: &mut Vec<Object>) -> String {
+        let handle = self.heap.objs[obj.hid].as_ref().unwrap();
+        match unsafe { &*handle.ptr } {
+            ObjectInner::Model { model_name, fields } => {
+                if path.contains(&obj) {
+                    return format!("<cycle {}#{}>", model_name, obj.hid);
+                }
+                if depth >= self.display_depth {
+                    return format!("{} {{ .. }}", model_name);
+                }
+
+                // declaration order if the model is known, else by name
+                let mut names: Vec<_> = fields.keys().cloned().collect();
+                if let Some(model) = self.models.get(model_name) {
+                    names.sort_by_key(|name| model.fields.iter().position(|field| field == name));
+                }
+
+                path.push(obj);
+                let fields: Vec<_> = names
+                    .into_iter()
+                    .map(|name| {
+                        let val = self.display_nested(fields[&name], depth + 1, path);
+                        (name, val)
+                    })
+                    .collect();
+                path.pop();
+                fmt_model(model_name, &fields)
+            }
+            ObjectInner::Tuple(elems) | ObjectInner::List(elems) => {
+                let is_tuple = matches!(unsafe { &*handle.ptr }, ObjectInner::Tuple(_));
+                if path.contains(&obj) {
+                    let kind = if is_tuple { "Tuple" } else { "List" };
+                    return format!("<cycle {}#{}>", kind, obj.hid);
+                }
+                if depth >= self.display_depth {
+                    return if is_tuple { "(..)" } else { "[..]" }.to_string();
+                }
+
+                path.push(obj);
+                let elems: Vec<_> = elems
+                    .iter()
+                    .map(|&elem| self.display_nested(elem, depth + 1, path))
+                    .collect();
+                path.pop();
+                fmt_elems(&elems, is_tuple)
+            }
+            ObjectInner::Func { params, .. } => format!("<func{}>", fmt_params(params)),
+            _ => self.heap.get_value(obj).to_string(),
+        }
+    }
+}
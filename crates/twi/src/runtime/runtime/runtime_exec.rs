@@ -1,14 +1,17 @@
 use parse::{
     ast::{
         expr::ExprNode,
-        stmt::{Lvalue, StmtNode},
+        stmt::{Lvalue, Param, Pattern, StmtNode},
     },
     types::literal::Literal,
 };
 
 use crate::{
     errors::{TwiError, TwiResult},
-    runtime::gc::{objects::ObjectInner, value::Value},
+    runtime::gc::{
+        objects::{Object, ObjectInner},
+        value::Value,
+    },
     scope::scope::ScopeType,
 };
 
@@ -19,7 +22,7 @@ impl Runtime {
         // for sc in &self.scopes {
         //     dbg!(&sc.vars);
         // }
-        if self.cur_scope().vars.contains_key(&ident) {
+        if self.cur_scope().is_bound(&ident) {
             return Err(TwiError::DuplicateLocalBind(ident));
         }
         let val = self.eval(expr)?;
@@ -29,16 +32,41 @@ impl Runtime {
         Ok(())
     }
 
+    pub fn exec_const(&mut self, ident: String, expr: ExprNode) -> TwiResult<()> {
+        if self.cur_scope().is_bound(&ident) {
+            return Err(TwiError::DuplicateLocalBind(ident));
+        }
+        let val = self.eval(expr)?;
+        self.cur_scope_mut().add_const(ident, val);
+
+        Ok(())
+    }
+
+    pub fn exec_let_tuple(&mut self, idents: Vec<String>, expr: ExprNode) -> TwiResult<()> {
+        for ident in &idents {
+            if self.cur_scope().is_bound(ident) {
+                return Err(TwiError::DuplicateLocalBind(ident.clone()));
+            }
+        }
+        let obj = self.eval(expr)?;
+        let elems = self.unpack(obj, idents.len())?;
+        for (ident, elem) in idents.into_iter().zip(elems) {
+            self.cur_scope_mut().add(ident, elem);
+        }
+
+        Ok(())
+    }
+
     pub fn exec_funcdef(
         &mut self,
         name: String,
-        params: Vec<String>,
+        params: Vec<Param>,
         body: Vec<StmtNode>,
     ) -> TwiResult<()> {
         // for sc in &self.scopes {
         //     dbg!(&sc.vars);
         // }
-        if self.cur_scope_mut().vars.contains_key(&name) {
+        if self.cur_scope().is_bound(&name) {
             return Err(TwiError::DuplicateLocalBind(name));
         }
         self.bind(name, ObjectInner::Func { params, body });
@@ -46,11 +74,14 @@ impl Runtime {
         Ok(())
     }
 
-    pub fn exec_print(&mut self, expr: ExprNode) -> TwiResult<()> {
-        let obj = self.eval(expr)?;
-        println!("[SL print] {}", self.heap.get_value(obj));
-
-        Ok(())
+    /// args are printed as text separated by spaces
+    pub fn exec_print(&mut self, exprs: Vec<ExprNode>) -> TwiResult<()> {
+        let mut texts = Vec::new();
+        for expr in exprs {
+            let obj = self.eval(expr)?;
+            texts.push(self.to_text(obj));
+        }
+        self.write_line(&texts.join(" "))
     }
 
     /// iter is placeholder
@@ -68,9 +99,8 @@ impl Runtime {
                 let sg = self.enter_scope(ScopeType::Block);
                 // define iter counter
                 self.exec_let(iter.clone(), ExprNode::Literal(Literal::Int(count)))?;
-                for stmt in &body {
-                    self.exec_stmt(stmt.clone())?;
-                }
+                let res = self.exec_block(body.clone());
+                sg.exit(res)?;
             }
             // dbg!(&self.global_scope.vars);
             Ok(())
@@ -92,9 +122,8 @@ impl Runtime {
                 // check if condition is true
                 if cnd {
                     let sg = self.enter_scope(ScopeType::Block);
-                    for stmt in body.clone() {
-                        self.exec_stmt(stmt.clone())?;
-                    }
+                    let res = self.exec_block(body.clone());
+                    sg.exit(res)?;
                 } else {
                     return Ok(());
                 }
@@ -135,16 +164,146 @@ impl Runtime {
         }
     }
 
+    pub fn exec_block(&mut self, body: Vec<StmtNode>) -> TwiResult<()> {
+        for stmt in body {
+            self.exec_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    pub fn exec_try(
+        &mut self,
+        body: Vec<StmtNode>,
+        catch: Option<(String, Vec<StmtNode>)>,
+        finally: Option<Vec<StmtNode>>,
+    ) -> TwiResult<()> {
+        let res = {
+            let sg = self.enter_scope(ScopeType::Block);
+            let res = self.exec_block(body);
+            sg.exit(res)
+        };
+        // the try scope is gone, keep its pending value alive through catch and finally
+        self.root_pending(&res);
+
+        let res = match (res, catch) {
+            (Err(e), Some((ident, catch_body))) if e.is_catchable() => {
+                self.backtrace = None;
+                let sg = self.enter_scope(ScopeType::Block);
+                let err = self.error_object(e);
+                self.cur_scope_mut().add(ident, err);
+                let res = self.exec_block(catch_body);
+                sg.exit(res)
+            }
+            (res, _) => res,
+        };
+        self.root_pending(&res);
+
+        // an error raised in finally replaces the pending result
+        if let Some(finally_body) = finally {
+            let sg = self.enter_scope(ScopeType::Block);
+            let finally_res = self.exec_block(finally_body);
+            sg.exit(finally_res)?;
+        }
+        res
+    }
+
+    /// Run the first arm whose pattern matches, with model fields bound in the arm's scope
+    pub fn exec_match(
+        &mut self,
+        expr: ExprNode,
+        arms: Vec<(Pattern, Vec<StmtNode>)>,
+    ) -> TwiResult<()> {
+        let obj = self.eval(expr)?;
+        for (pattern, body) in arms {
+            if let Some(bindings) = self.match_pattern(obj, &pattern)? {
+                let sg = self.enter_scope(ScopeType::Block);
+                for (ident, field_obj) in bindings {
+                    self.cur_scope_mut().add(ident, field_obj);
+                }
+                let res = self.exec_block(body);
+                return sg.exit(res);
+            }
+        }
+        Err(TwiError::NoMatchingArm(
+            self.heap.get_value(obj).to_string(),
+        ))
+    }
+
+    /// The bindings made by `pattern` if it matches `obj`
+    fn match_pattern(
+        &self,
+        obj: Object,
+        pattern: &Pattern,
+    ) -> TwiResult<Option<Vec<(String, Object)>>> {
+        let matched = match (pattern, self.heap.get_value(obj)) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Literal(lit), val) => match (lit, val) {
+                (Literal::Int(x), Value::Int(y)) => *x == y,
+                (Literal::Float(x), Value::Float(y)) => *x == y,
+                (Literal::String(x), Value::String(y)) => *x == y,
+                (Literal::Bool(x), Value::Bool(y)) => *x == y,
+                (Literal::Teer(x), Value::Teer(y)) => *x == y,
+                (Literal::Nil, Value::Nil) => true,
+                _ => false,
+            },
+            (
+                Pattern::Model { name, bindings },
+                Value::Model {
+                    name: model_name, ..
+                },
+            ) if *name == model_name => {
+                let mut bound = Vec::new();
+                for (field, ident) in bindings {
+                    let (field_obj, _) = self.heap.member(obj, field.clone())?;
+                    bound.push((ident.clone(), field_obj));
+                }
+                return Ok(Some(bound));
+            }
+            _ => false,
+        };
+        Ok(matched.then(Vec::new))
+    }
+
+    /// The value bound by `catch`: thrown values as is,
+    /// runtime errors as an `Error` model with `kind` and `message`
+    fn error_object(&mut self, err: TwiError) -> Object {
+        if let TwiError::Throw(obj) = err {
+            return obj;
+        }
+        let kind = self.alloc(ObjectInner::String(err.kind().to_string()));
+        let message = self.alloc(ObjectInner::String(err.to_string()));
+        self.alloc(ObjectInner::Model {
+            model_name: "Error".into(),
+            fields: [("kind".to_string(), kind), ("message".to_string(), message)]
+                .into_iter()
+                .collect(),
+        })
+    }
+
     pub fn exec_assign(&mut self, target: Lvalue, expr: ExprNode) -> TwiResult<()> {
+        // order is critical to fool borrow checker...
+        let val = self.eval(expr)?;
+        self.assign(target, val)
+    }
+
+    /// the right hand side is fully evaluated first, so `a, b = b, a` swaps
+    pub fn exec_assign_tuple(&mut self, targets: Vec<Lvalue>, expr: ExprNode) -> TwiResult<()> {
+        let obj = self.eval(expr)?;
+        let elems = self.unpack(obj, targets.len())?;
+        for (target, elem) in targets.into_iter().zip(elems) {
+            self.assign(target, elem)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign(&mut self, target: Lvalue, val: Object) -> TwiResult<()> {
         match target {
             Lvalue::Identifier(ident) => {
-                // order is critical to fool borrow checker...
-                let val = self.eval(expr)?;
                 let objref = self.getvar_mut(ident)?;
                 *objref = val;
             }
             Lvalue::Member { base, members } => {
-                let val = self.eval(expr)?;
                 let mut obj = self.getvar(base)?;
                 obj.refs(&mut self.heap, members, val)?;
             }
@@ -1,39 +1,107 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io, mem,
+};
 
 use log::info;
 use parse::ast::stmt::StmtNode;
 
 use crate::{
-    errors::{TwiError, TwiResult},
+    capabilities::Capabilities,
+    errors::{arg_count, TwiError, TwiResult},
     runtime::gc::{gc::Heap, objects::ObjectInner, timer::Timer},
     scope::scope::{Scope, ScopeType},
 };
 
-use super::runtime::{GlobalVar, Model, Runtime};
+use super::{
+    runtime::{Frame, GlobalVar, Model, Runtime},
+    runtime_check::check_consts,
+};
+
+/// Calls that may be in progress at once unless set otherwise
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
 
 impl Runtime {
-    //
-    pub fn structure(statements: Vec<StmtNode>) -> TwiResult<Self> {
+    /// Build the runtime from top-level statements.
+    /// Without a `main` function (or with `script` set), top-level statements
+    /// run in order in the global scope. With `main`, global initializers are kept
+    /// for `run`, so errors in them are runtime errors and the configuration applies.
+    pub fn structure(statements: Vec<StmtNode>, script: bool) -> TwiResult<Self> {
+        check_consts(&statements)?;
+
         let mut rt = Self {
             models: BTreeMap::new(),
             program: Vec::new(),
+            globals: Vec::new(),
             heap: Heap::new(),
             scopes: vec![],
             gc_interval: 0.8,
             gc_timer: Timer::new(),
+            display_depth: 8,
+            output: Box::new(io::stdout()),
+            plain: false,
+            input: Box::new(io::BufReader::new(io::stdin())),
+            caps: Capabilities::default(),
+            frames: vec![Frame::outermost("<script>".into())],
+            backtrace: None,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            main_param: None,
             global_scope: Scope {
                 scope_type: ScopeType::Global,
                 vars: BTreeMap::new(),
+                consts: BTreeSet::new(),
+                builtins: BTreeSet::new(),
                 unnamed: Vec::new(),
+                deferred: Vec::new(),
             },
         };
 
+        rt.install_stdlib();
+
+        // builtin model of errors caught by `try`, user models may shadow it
+        rt.models.insert(
+            "Error".into(),
+            Model {
+                name: "Error".into(),
+                fields: vec!["kind".into(), "message".into()],
+            },
+        );
+
+        let has_main = statements
+            .iter()
+            .any(|stmt| matches!(stmt, StmtNode::FuncDef { name, .. } if name == "main"));
+
+        if script || !has_main {
+            for stmt in statements {
+                match stmt {
+                    // models are hoisted, everything else runs in order
+                    StmtNode::Model { name, fields } => {
+                        rt.models.insert(name.clone(), Model { name, fields });
+                    }
+                    s => rt.program.push(s),
+                }
+            }
+            return Ok(rt);
+        }
+
         for stmt in statements {
             match stmt {
                 StmtNode::FuncDef { name, params, body } => {
                     // main
                     if name == "main" {
+                        if params.len() > 1 {
+                            return Err(TwiError::ArgNumMismatch {
+                                funcname: name,
+                                expected: arg_count(0, Some(1)),
+                                got: params.len(),
+                            });
+                        }
                         rt.program = body;
+                        // main runs in its own call scope, its param holds the program args
+                        rt.scopes.push(Scope::call());
+                        rt.frames = vec![Frame::outermost(name)];
+                        rt.main_param = params.into_iter().next().map(|param| param.name);
+                        rt.set_args(Vec::new());
                         return Ok(rt);
                     } else {
                         // bind in global scope
@@ -44,10 +112,9 @@ impl Runtime {
                 StmtNode::Model { name, fields } => {
                     rt.models.insert(name.clone(), Model { name, fields });
                 }
-                StmtNode::Let { ident, expr } => {
-                    // bind in global scope
-                    let obj = rt.eval(expr)?;
-                    rt.global_scope.add(ident, obj);
+                // evaluated by `run`, once the runtime is configured
+                s @ (StmtNode::Let { .. } | StmtNode::Const { .. } | StmtNode::LetTuple { .. }) => {
+                    rt.globals.push(s)
                 }
                 s => return Err(TwiError::InvalidGlobalDefinition(format!("{:?}", s))),
             }
@@ -56,13 +123,88 @@ impl Runtime {
         Err(TwiError::MainNotFound)
     }
 
-    pub fn run(&mut self) -> TwiResult<()> {
-        for stmt in self.program.clone() {
-            let stmt = stmt.clone();
-            self.exec_stmt(stmt)?;
+    /// Run more top-level statements in the global scope, as in script mode.
+    /// A top-level `return` ends them early.
+    pub fn load(&mut self, statements: Vec<StmtNode>) -> TwiResult<()> {
+        check_consts(&statements)?;
+
+        let mut program = Vec::new();
+        for stmt in statements {
+            match stmt {
+                StmtNode::Model { name, fields } => {
+                    self.models.insert(name.clone(), Model { name, fields });
+                }
+                s => program.push(s),
+            }
+        }
+        self.backtrace = None;
+        let res = self.exec_block(program);
+        self.record_backtrace(&res);
+        match self.run_deferred(res) {
+            Err(TwiError::Return(_)) => Ok(()),
+            res => res,
+        }
+    }
+
+    /// Evaluate the global initializers in order, outside of `main`'s scope and frame
+    fn init_globals(&mut self) -> TwiResult<()> {
+        let main_scopes = mem::take(&mut self.scopes);
+        let main_frames = mem::replace(&mut self.frames, vec![Frame::outermost("<script>".into())]);
+        let res = self.bind_globals();
+        self.record_backtrace(&res);
+        self.scopes = main_scopes;
+        self.frames = main_frames;
+        res
+    }
+
+    fn bind_globals(&mut self) -> TwiResult<()> {
+        for stmt in mem::take(&mut self.globals) {
+            match stmt {
+                StmtNode::Let { ident, expr } => {
+                    // bind in global scope
+                    let obj = self.eval(expr)?;
+                    self.global_scope.add(ident, obj);
+                }
+                StmtNode::Const { ident, expr } => {
+                    let obj = self.eval(expr)?;
+                    self.global_scope.add_const(ident, obj);
+                }
+                StmtNode::LetTuple { idents, expr } => {
+                    let obj = self.eval(expr)?;
+                    let elems = self.unpack(obj, idents.len())?;
+                    for (ident, elem) in idents.into_iter().zip(elems) {
+                        self.global_scope.add(ident, elem);
+                    }
+                }
+                s => unreachable!("only initializers are kept in globals, got {:?}", s),
+            }
         }
         Ok(())
     }
+
+    /// Bind the param of `main(args)` to `args`, as a list of strings.
+    /// Nothing happens without a `main` taking a param.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        let Some(param) = self.main_param.clone() else {
+            return;
+        };
+        let elems = args
+            .into_iter()
+            .map(|arg| self.heap.alloc(ObjectInner::String(arg)))
+            .collect();
+        let list = self.heap.alloc(ObjectInner::List(elems));
+        self.scopes[0].add(param, list);
+    }
+
+    pub fn run(&mut self) -> TwiResult<()> {
+        self.backtrace = None;
+        let res = self
+            .init_globals()
+            .and_then(|_| self.exec_block(self.program.clone()));
+        self.record_backtrace(&res);
+        // the outermost scope is never left, its deferred statements run when the program ends
+        self.run_deferred(res)
+    }
 }
 
 impl Runtime {
@@ -78,11 +220,17 @@ impl Runtime {
             StmtNode::Let { ident, expr } => {
                 self.exec_let(ident, expr)?;
             }
+            StmtNode::Const { ident, expr } => {
+                self.exec_const(ident, expr)?;
+            }
+            StmtNode::LetTuple { idents, expr } => {
+                self.exec_let_tuple(idents, expr)?;
+            }
             StmtNode::For { iter, n_iter, body } => {
                 //
                 self.exec_for(iter, n_iter, body)?;
             }
-            StmtNode::Print { expr } => self.exec_print(expr)?,
+            StmtNode::Print { exprs } => self.exec_print(exprs)?,
             StmtNode::While { cond, body } => {
                 self.exec_while(cond, body)?;
             }
@@ -93,10 +241,21 @@ impl Runtime {
                 // return value as error
                 return Err(TwiError::Return(self.eval(expr)?));
             }
+            StmtNode::Throw { expr } => {
+                return Err(TwiError::Throw(self.eval(expr)?));
+            }
+            StmtNode::Try {
+                body,
+                catch,
+                finally,
+            } => self.exec_try(body, catch, finally)?,
+            StmtNode::Match { expr, arms } => self.exec_match(expr, arms)?,
+            StmtNode::Defer { stmt } => self.cur_scope_mut().deferred.push(*stmt),
             StmtNode::If { cond, body } => {
                 //
                 let sg = self.enter_scope(ScopeType::Block);
-                self.exec_if_else(cond, body, Vec::new())?;
+                let res = self.exec_if_else(cond, body, Vec::new());
+                sg.exit(res)?;
             }
             StmtNode::IfElse {
                 cond,
@@ -104,20 +263,21 @@ impl Runtime {
                 else_body,
             } => {
                 let sg = self.enter_scope(ScopeType::Block);
-                self.exec_if_else(cond, if_body, else_body)?;
+                let res = self.exec_if_else(cond, if_body, else_body);
+                sg.exit(res)?;
             }
             StmtNode::Scope { body } => {
                 info!("scope.len={}", self.scopes.len());
                 let sg = self.enter_scope(ScopeType::Block);
-                for stmt in body {
-                    self.exec_stmt(stmt.clone())?;
-                }
+                let res = self.exec_block(body);
+                sg.exit(res)?;
             }
             StmtNode::FuncDef { name, params, body } => self.exec_funcdef(name, params, body)?,
             StmtNode::Model { name: _, fields: _ } => {
                 return Err(TwiError::UnexpectedStatement("Model".into()))
             }
             StmtNode::Assign { target, expr } => self.exec_assign(target, expr)?,
+            StmtNode::AssignTuple { targets, expr } => self.exec_assign_tuple(targets, expr)?,
         }
         Ok(())
     }
@@ -0,0 +1,430 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    rc::Rc,
+};
+
+use lex::token::teer;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive};
+
+use crate::{
+    capabilities::io_error,
+    errors::{arg_count, TwiError, TwiResult},
+    runtime::{
+        gc::{
+            objects::{NativeFunc, Object, ObjectInner},
+            value::Value,
+        },
+        runtime::eval::eval_bool::{value_order, Ordering},
+    },
+};
+
+use super::runtime::Runtime;
+
+type StdFn = fn(&mut Runtime, &[Object]) -> TwiResult<Object>;
+
+/// Natives bound in the global scope before the program runs, with their number
+/// of fixed args and whether they take any number of args after those.
+/// Programs may bind the same names to replace them.
+const STDLIB: &[(&str, usize, bool, StdFn)] = &[
+    // conversion
+    ("bool", 1, false, std_bool),
+    ("teer", 1, false, std_teer),
+    ("int", 1, false, std_int),
+    ("float", 1, false, std_float),
+    ("str", 1, false, std_str),
+    ("len", 1, false, std_len),
+    // math
+    ("abs", 1, false, std_abs),
+    ("min", 1, true, std_min),
+    ("max", 1, true, std_max),
+    ("sqrt", 1, false, std_sqrt),
+    ("pow", 2, false, std_pow),
+    ("floor", 1, false, std_floor),
+    // string
+    ("format", 1, true, std_format),
+    ("split", 2, false, std_split),
+    ("join", 2, false, std_join),
+    ("trim", 1, false, std_trim),
+    ("find", 2, false, std_find),
+    ("replace", 3, false, std_replace),
+    ("upper", 1, false, std_upper),
+    ("lower", 1, false, std_lower),
+    // io, see `Capabilities`
+    ("read_line", 0, false, std_read_line),
+    ("read_file", 1, false, std_read_file),
+    ("write_file", 2, false, std_write_file),
+    ("append_file", 2, false, std_append_file),
+    ("exists", 1, false, std_exists),
+];
+
+impl Runtime {
+    pub(crate) fn install_stdlib(&mut self) {
+        for &(name, arity, variadic, func) in STDLIB {
+            self.bind_native(NativeFunc {
+                name: name.into(),
+                arity,
+                variadic,
+                func: Rc::new(func),
+            });
+        }
+    }
+
+    /// Expose a Rust function to the program as a global taking exactly `arity` args.
+    /// Like the stdlib, the program may bind the same name to replace it.
+    /// Objects made for the result should come from [`Runtime::alloc_value`],
+    /// which keeps them alive until the calling scope exits.
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, func: F)
+    where
+        F: Fn(&mut Runtime, &[Object]) -> TwiResult<Object> + 'static,
+    {
+        self.bind_native(NativeFunc {
+            name: name.into(),
+            arity,
+            variadic: false,
+            func: Rc::new(func),
+        });
+    }
+
+    fn bind_native(&mut self, native: NativeFunc) {
+        let name = native.name.clone();
+        let obj = self.heap.alloc(ObjectInner::Native(native));
+        self.global_scope.add_builtin(name, obj);
+    }
+
+    /// Natives take positional args only
+    pub(crate) fn call_native(
+        &mut self,
+        native: NativeFunc,
+        args: Vec<Object>,
+        named: Vec<(String, Object)>,
+    ) -> TwiResult<Object> {
+        if let Some((name, _)) = named.into_iter().next() {
+            return Err(TwiError::UnknownNamedArg {
+                funcname: native.name,
+                name,
+            });
+        }
+        if args.len() < native.arity || (args.len() > native.arity && !native.variadic) {
+            return Err(TwiError::ArgNumMismatch {
+                funcname: native.name,
+                expected: arg_count(native.arity, (!native.variadic).then_some(native.arity)),
+                got: args.len(),
+            });
+        }
+        (native.func)(self, &args)
+    }
+}
+
+fn string_arg(rt: &Runtime, obj: Object) -> TwiResult<String> {
+    match rt.heap.get_value(obj) {
+        Value::String(s) => Ok(s),
+        val => Err(TwiError::UnexpectedType {
+            expected: "String".into(),
+            got: val.to_string(),
+        }),
+    }
+}
+
+fn float_arg(rt: &Runtime, obj: Object) -> TwiResult<f64> {
+    match rt.heap.get_value(obj) {
+        Value::Int(x) => Ok(x as f64),
+        Value::BigInt(x) => Ok(x.to_f64().unwrap_or(f64::NAN)),
+        Value::Float(x) => Ok(x),
+        val => Err(TwiError::UnexpectedType {
+            expected: "Int or Float".into(),
+            got: val.to_string(),
+        }),
+    }
+}
+
+// ############### conversion ###############
+
+/// excel is true and exile is false, empty has no bool value
+fn std_bool(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let b = match rt.heap.get_value(args[0]) {
+        Value::Bool(b) => b,
+        Value::Teer(teer::excel) => true,
+        Value::Teer(teer::exile) => false,
+        val => {
+            return Err(TwiError::InvalidConversion {
+                value: val.to_string(),
+                target: "bool".into(),
+            })
+        }
+    };
+    Ok(rt.alloc(ObjectInner::Bool(b)))
+}
+
+/// true is excel and false is exile, nil is the unknown empty
+fn std_teer(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let t = match rt.heap.get_value(args[0]) {
+        Value::Teer(t) => t,
+        Value::Bool(true) => teer::excel,
+        Value::Bool(false) => teer::exile,
+        Value::Nil => teer::empty,
+        val => {
+            return Err(TwiError::InvalidConversion {
+                value: val.to_string(),
+                target: "teer".into(),
+            })
+        }
+    };
+    Ok(rt.alloc(ObjectInner::Teer(t)))
+}
+
+/// floats are truncated toward zero, strings are parsed, true is 1 and false is 0
+fn std_int(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let val = rt.heap.get_value(args[0]);
+    let x = match &val {
+        Value::Int(x) => Some(BigInt::from(*x)),
+        Value::BigInt(x) => Some(x.clone()),
+        Value::Float(x) => BigInt::from_f64(x.trunc()),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Bool(b) => Some(BigInt::from(*b as i64)),
+        _ => None,
+    };
+    match x {
+        Some(x) => Ok(rt.alloc_int(x)),
+        None => Err(TwiError::InvalidConversion {
+            value: val.to_string(),
+            target: "int".into(),
+        }),
+    }
+}
+
+fn std_float(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let val = rt.heap.get_value(args[0]);
+    let x = match &val {
+        Value::Int(x) => Some(*x as f64),
+        Value::BigInt(x) => x.to_f64(),
+        Value::Float(x) => Some(*x),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    match x {
+        Some(x) => Ok(rt.alloc(ObjectInner::Float(x))),
+        None => Err(TwiError::InvalidConversion {
+            value: val.to_string(),
+            target: "float".into(),
+        }),
+    }
+}
+
+/// the text `print` would show
+fn std_str(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let text = rt.to_text(args[0]);
+    Ok(rt.alloc(ObjectInner::String(text)))
+}
+
+fn std_len(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    rt.len_of(args[0])
+}
+
+// ############### math ###############
+
+fn std_abs(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    match rt.heap.get_value(args[0]) {
+        Value::Int(x) => match x.checked_abs() {
+            Some(x) => Ok(rt.alloc(ObjectInner::Int(x))),
+            None => Ok(rt.alloc_int(BigInt::from(x).abs())),
+        },
+        Value::BigInt(x) => Ok(rt.alloc_int(x.abs())),
+        Value::Float(x) => Ok(rt.alloc(ObjectInner::Float(x.abs()))),
+        val => Err(TwiError::UnexpectedType {
+            expected: "Int or Float".into(),
+            got: val.to_string(),
+        }),
+    }
+}
+
+/// The first of the args that no other arg is `better` than,
+/// a single tuple or list arg stands for its elements
+fn extreme(
+    rt: &mut Runtime,
+    funcname: &str,
+    args: &[Object],
+    better: fn(Ordering) -> bool,
+) -> TwiResult<Object> {
+    let candidates = match args {
+        [arg] => rt.heap.elements(*arg)?,
+        args => args.to_vec(),
+    };
+    let Some((&first, rest)) = candidates.split_first() else {
+        return Err(TwiError::InvalidArgument {
+            funcname: funcname.into(),
+            message: "no values to compare".into(),
+        });
+    };
+
+    let mut best = first;
+    for &candidate in rest {
+        let ord = value_order(rt.heap.get_value(candidate), rt.heap.get_value(best))?;
+        if better(ord) {
+            best = candidate;
+        }
+    }
+    Ok(best)
+}
+
+fn std_min(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    extreme(rt, "min", args, |ord| ord.lt())
+}
+
+fn std_max(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    extreme(rt, "max", args, |ord| ord.gt())
+}
+
+fn std_sqrt(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let x = float_arg(rt, args[0])?;
+    Ok(rt.alloc(ObjectInner::Float(x.sqrt())))
+}
+
+/// same as `**`
+fn std_pow(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let base = rt.heap.get_value(args[0]);
+    let exp = rt.heap.get_value(args[1]);
+    rt.pow_values(base, exp)
+}
+
+/// the largest int not greater than the arg
+fn std_floor(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    match rt.heap.get_value(args[0]) {
+        Value::Int(_) | Value::BigInt(_) => Ok(args[0]),
+        Value::Float(x) => match BigInt::from_f64(x.floor()) {
+            Some(x) => Ok(rt.alloc_int(x)),
+            None => Err(TwiError::InvalidConversion {
+                value: x.to_string(),
+                target: "int".into(),
+            }),
+        },
+        val => Err(TwiError::UnexpectedType {
+            expected: "Int or Float".into(),
+            got: val.to_string(),
+        }),
+    }
+}
+
+// ############### string ###############
+
+fn std_format(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    rt.format_values(args[0], &args[1..])
+}
+
+/// `split(s, sep)`: the parts of `s` between occurrences of `sep`, as a list
+fn std_split(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    let sep = string_arg(rt, args[1])?;
+    if sep.is_empty() {
+        return Err(TwiError::InvalidArgument {
+            funcname: "split".into(),
+            message: "empty separator".into(),
+        });
+    }
+
+    let parts = s
+        .split(sep.as_str())
+        .map(|part| rt.alloc(ObjectInner::String(part.to_string())))
+        .collect();
+    Ok(rt.alloc(ObjectInner::List(parts)))
+}
+
+/// `join(elems, sep)`: the text of each element, separated by `sep`
+fn std_join(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let elems = rt.heap.elements(args[0])?;
+    let sep = string_arg(rt, args[1])?;
+
+    let texts: Vec<_> = elems.into_iter().map(|elem| rt.to_text(elem)).collect();
+    Ok(rt.alloc(ObjectInner::String(texts.join(&sep))))
+}
+
+fn std_trim(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    Ok(rt.alloc(ObjectInner::String(s.trim().to_string())))
+}
+
+/// `find(s, sub)`: character index of the first occurrence of `sub`, or -1
+fn std_find(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    let sub = string_arg(rt, args[1])?;
+
+    let index = match s.find(sub.as_str()) {
+        Some(byte_index) => s[..byte_index].chars().count() as i64,
+        None => -1,
+    };
+    Ok(rt.alloc(ObjectInner::Int(index)))
+}
+
+/// `replace(s, from, to)`: every occurrence of `from` replaced by `to`
+fn std_replace(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    let from = string_arg(rt, args[1])?;
+    let to = string_arg(rt, args[2])?;
+    Ok(rt.alloc(ObjectInner::String(s.replace(from.as_str(), &to))))
+}
+
+fn std_upper(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    Ok(rt.alloc(ObjectInner::String(s.to_uppercase())))
+}
+
+fn std_lower(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let s = string_arg(rt, args[0])?;
+    Ok(rt.alloc(ObjectInner::String(s.to_lowercase())))
+}
+
+// ############### io ###############
+
+/// The next line of input without its line break, nil at the end of input
+fn std_read_line(rt: &mut Runtime, _args: &[Object]) -> TwiResult<Object> {
+    rt.caps.check_stdin()?;
+
+    let mut line = String::new();
+    let n = rt
+        .input
+        .read_line(&mut line)
+        .map_err(|e| TwiError::Io(e.to_string()))?;
+    if n == 0 {
+        return Ok(rt.alloc(ObjectInner::Nil));
+    }
+    let line = line.strip_suffix('\n').unwrap_or(&line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    Ok(rt.alloc(ObjectInner::String(line.to_string())))
+}
+
+fn std_read_file(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let path = rt.caps.resolve(&string_arg(rt, args[0])?)?;
+    let text = fs::read_to_string(&path).map_err(io_error(&path))?;
+    Ok(rt.alloc(ObjectInner::String(text)))
+}
+
+/// `write_file(path, s)`: replaces the file's contents with `s`
+fn std_write_file(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let path = rt.caps.resolve(&string_arg(rt, args[0])?)?;
+    let text = string_arg(rt, args[1])?;
+    fs::write(&path, text).map_err(io_error(&path))?;
+    Ok(rt.alloc(ObjectInner::Nil))
+}
+
+/// `append_file(path, s)`: adds `s` to the end of the file, creating it if needed
+fn std_append_file(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let path = rt.caps.resolve(&string_arg(rt, args[0])?)?;
+    let text = string_arg(rt, args[1])?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(io_error(&path))?;
+    Ok(rt.alloc(ObjectInner::Nil))
+}
+
+fn std_exists(rt: &mut Runtime, args: &[Object]) -> TwiResult<Object> {
+    let exists = match rt.caps.resolve(&string_arg(rt, args[0])?) {
+        Ok(path) => path.exists(),
+        Err(TwiError::Io(_)) => false,
+        Err(e) => return Err(e),
+    };
+    Ok(rt.alloc(ObjectInner::Bool(exists)))
+}
@@ -1,4 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use parse::ast::stmt::StmtNode;
 
 use crate::runtime::gc::objects::Object;
 
@@ -13,7 +15,13 @@ pub enum ScopeType {
 pub struct Scope {
     pub(crate) scope_type: ScopeType,
     pub(crate) vars: BTreeMap<String, Object>,
+    /// names bound with `const`, cannot be reassigned
+    pub(crate) consts: BTreeSet<String>,
+    /// names bound to stdlib natives, which the program may bind again
+    pub(crate) builtins: BTreeSet<String>,
     pub(crate) unnamed: Vec<Object>,
+    /// statements registered by `defer`, run in reverse order when the scope exits
+    pub(crate) deferred: Vec<StmtNode>,
 }
 
 impl Scope {
@@ -22,7 +30,10 @@ impl Scope {
         Self {
             scope_type: ScopeType::Block,
             vars: BTreeMap::new(),
+            consts: BTreeSet::new(),
+            builtins: BTreeSet::new(),
             unnamed: Vec::new(),
+            deferred: Vec::new(),
         }
     }
 
@@ -30,16 +41,36 @@ impl Scope {
         Self {
             scope_type: ScopeType::Call,
             vars: BTreeMap::new(),
+            consts: BTreeSet::new(),
+            builtins: BTreeSet::new(),
             unnamed: Vec::new(),
+            deferred: Vec::new(),
         }
     }
 }
 
 impl Scope {
     pub fn add(&mut self, ident: String, obj: Object) {
+        self.builtins.remove(&ident);
         self.vars.insert(ident, obj);
     }
 
+    pub fn add_const(&mut self, ident: String, obj: Object) {
+        self.builtins.remove(&ident);
+        self.consts.insert(ident.clone());
+        self.vars.insert(ident, obj);
+    }
+
+    pub fn add_builtin(&mut self, ident: String, obj: Object) {
+        self.builtins.insert(ident.clone());
+        self.vars.insert(ident, obj);
+    }
+
+    /// Whether binding `ident` here again is a duplicate, builtins may be replaced once
+    pub fn is_bound(&self, ident: &str) -> bool {
+        self.vars.contains_key(ident) && !self.builtins.contains(ident)
+    }
+
     pub fn get(&self, ident: &str) -> Option<Object> {
         self.vars.get(ident).map(|x| *x)
     }
@@ -1,9 +1,17 @@
-use crate::runtime::runtime::runtime::Runtime;
+use crate::{errors::TwiResult, runtime::runtime::runtime::Runtime};
 
 pub struct ScopeGuard {
     pub(crate) rt: *mut Runtime,
 }
 
+impl ScopeGuard {
+    /// Leave the scope with the result of its body, running its deferred statements first
+    pub(crate) fn exit<T>(self, res: TwiResult<T>) -> TwiResult<T> {
+        let rt = unsafe { &mut *self.rt };
+        rt.run_deferred(res)
+    }
+}
+
 impl Drop for ScopeGuard {
     fn drop(&mut self) {
         let rt = unsafe { &mut *self.rt };
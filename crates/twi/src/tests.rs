@@ -1,8 +1,33 @@
 use colored::Colorize;
 use lex::pest_parse::sl_parse_file;
+use lex::token::teer;
+use num_bigint::BigInt;
 use parse::parser::parser::Parser;
 
-use crate::{run_program, runtime::runtime::runtime::Runtime, InterpreterConfig};
+use crate::{
+    convert::ToSl,
+    engine::Engine,
+    errors::{TwiError, TwiResult},
+    repl::Repl,
+    run_program,
+    runtime::{gc::value::Value, runtime::runtime::Runtime},
+    Capabilities, CapturedOutput, InterpreterConfig, EXIT_COMPILE_ERROR, EXIT_RUNTIME_ERROR,
+    EXIT_UNCAUGHT,
+};
+
+/// Run a source file and return the value it `return`s (nil if it falls through)
+fn exec_source(path: &str, script: bool) -> TwiResult<Value> {
+    let tokens = sl_parse_file(path);
+    let root = Parser::new_from_iter(tokens)
+        .parse_stmt()
+        .expect("failed to compile");
+    let mut rt = Runtime::try_new(root, 0.8, script)?;
+    match rt.run() {
+        Ok(_) => Ok(Value::Nil),
+        Err(TwiError::Return(val)) => Ok(rt.heap.get_value(val)),
+        Err(e) => Err(e),
+    }
+}
 
 #[test]
 fn test_try() {
@@ -30,7 +55,7 @@ fn test_rt_base() {
         }
     };
 
-    let rt = Runtime::try_new(root, 0.8);
+    let rt = Runtime::try_new(root, 0.8, false);
     let mut rt = match rt {
         Ok(rt) => rt,
         Err(e) => {
@@ -59,3 +84,861 @@ fn test_exec_1() {
 fn test_fib() {
     run_program(InterpreterConfig::default("../../test_sources/fib.sl"));
 }
+
+#[test]
+fn test_script_mode() {
+    let val = exec_source("../../test_sources/script.sl", false).unwrap();
+    assert!(matches!(val, Value::Int(6)));
+}
+
+/// `*` `/` `%` bind tighter than `+` `-`, and both are left associative
+#[test]
+fn test_arith_precedence() {
+    let val = exec_source("../../test_sources/precedence.sl", false).unwrap();
+    let Value::Tuple(elems) = val else {
+        panic!("expected tuple, got {}", val);
+    };
+    let ints: Vec<_> = elems[..6]
+        .iter()
+        .map(|elem| match elem {
+            Value::Int(x) => *x,
+            v => panic!("expected int, got {}", v),
+        })
+        .collect();
+    assert_eq!(ints, [10, 14, 5, 2, 2, -5]);
+    assert!(matches!(elems[6], Value::Bool(true)));
+    assert!(matches!(elems[7], Value::Bool(false)));
+}
+
+#[test]
+fn test_const() {
+    let val = exec_source("../../test_sources/const.sl", false).unwrap();
+    assert!(matches!(val, Value::Int(15)));
+
+    let err = exec_source("../../test_sources/const_reassign.sl", false).unwrap_err();
+    assert!(matches!(err, TwiError::AssignToConst(ident) if ident == "limit"));
+}
+
+#[test]
+fn test_tuple() {
+    let val = exec_source("../../test_sources/tuple.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(2)));
+            assert!(matches!(elems[1], Value::Int(3)));
+            assert!(matches!(&elems[2], Value::Tuple(unit) if unit.is_empty()));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+
+    let err = exec_source("../../test_sources/tuple_arity.sl", false).unwrap_err();
+    assert!(matches!(
+        err,
+        TwiError::TupleArityMismatch {
+            expected: 3,
+            got: 2
+        }
+    ));
+}
+
+#[test]
+fn test_params() {
+    let val = exec_source("../../test_sources/params.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(9)));
+            assert!(matches!(elems[1], Value::Int(6)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+
+    let err = exec_source("../../test_sources/params_missing.sl", false).unwrap_err();
+    assert!(matches!(err, TwiError::MissingArg { param, .. } if param == "name"));
+
+    // too many args are reported against the range of positional args
+    let mut repl = Repl::new(&InterpreterConfig::default("unused.sl"));
+    repl.eval("func greet(name, greeting = \"hello\") { return greeting; }")
+        .unwrap();
+    let err = repl.eval("greet(\"a\", \"b\", \"c\")").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "In function greet: number of args mismatch: expected 1 to 2, got 3"
+    );
+    let err = repl.eval("len()").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "In function len: number of args mismatch: expected 1, got 0"
+    );
+}
+
+#[test]
+fn test_exceptions() {
+    let val = exec_source("../../test_sources/exceptions.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(&elems[0], Value::String(s) if s == "zero"));
+            assert!(matches!(&elems[1], Value::String(s) if s == "DivisionByZero"));
+            assert!(matches!(elems[2], Value::Bool(true)));
+            match &elems[3] {
+                Value::Tuple(nested) => {
+                    assert!(matches!(&nested[0], Value::String(s) if s == "finally ran"));
+                    assert!(matches!(&nested[1], Value::String(s) if s == "custom"));
+                }
+                v => panic!("expected tuple, got {}", v),
+            }
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+
+    let err = exec_source("../../test_sources/uncaught.sl", false).unwrap_err();
+    assert!(matches!(err, TwiError::Throw(_)));
+
+    let err = TwiError::SliceOutOfBounds {
+        start: 2,
+        end: 1,
+        len: 3,
+    };
+    assert_eq!(err.kind(), "SliceOutOfBounds");
+    assert_eq!(TwiError::DivisionByZero.kind(), "DivisionByZero");
+
+    // errors in the interpreter rather than the program pass through `try`
+    let mut repl = Repl::new(&InterpreterConfig::default("unused.sl"));
+    repl.eval("func f() { try { model M { a, } } catch e { return e.kind; } return nil; }")
+        .unwrap();
+    let err = repl.eval("f()").unwrap_err();
+    assert!(matches!(err, TwiError::UnexpectedStatement(_)));
+}
+
+/// Values returned or thrown from `try` stay alive while `catch` and `finally` run,
+/// with a collection before every statement
+#[test]
+fn test_try_gc() {
+    let tokens = sl_parse_file("../../test_sources/try_gc.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.0, false).unwrap();
+    let Err(TwiError::Return(val)) = rt.run() else {
+        panic!("expected a return value");
+    };
+    assert_eq!(
+        rt.value(val).to_string(),
+        "(\"value!\", \"boomx\", \"boomxy\")"
+    );
+}
+
+#[test]
+fn test_teer_logic() {
+    let val = exec_source("../../test_sources/teer.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Teer(teer::empty)));
+            assert!(matches!(elems[1], Value::Teer(teer::exile)));
+            assert!(matches!(elems[2], Value::Teer(teer::excel)));
+            assert!(matches!(elems[3], Value::Teer(teer::empty)));
+            assert!(matches!(elems[4], Value::Teer(teer::exile)));
+            assert!(matches!(elems[5], Value::Bool(true)));
+            assert!(matches!(elems[6], Value::Bool(true)));
+            assert!(matches!(elems[7], Value::Bool(false)));
+            assert!(matches!(&elems[8], Value::String(s) if s == "InvalidConversion"));
+            assert!(matches!(elems[9], Value::Int(7)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_cmp() {
+    let val = exec_source("../../test_sources/cmp.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Teer(teer::excel)));
+            assert!(matches!(elems[1], Value::Teer(teer::empty)));
+            assert!(matches!(elems[2], Value::Teer(teer::exile)));
+            assert!(matches!(elems[3], Value::Teer(teer::excel)));
+            assert!(matches!(elems[4], Value::Teer(teer::exile)));
+            assert!(matches!(elems[5], Value::Teer(teer::empty)));
+            assert!(matches!(elems[6], Value::Teer(teer::excel)));
+            assert!(matches!(elems[7], Value::Teer(teer::excel)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_match() {
+    let val = exec_source("../../test_sources/match.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(&elems[0], Value::String(s) if s == "exile"));
+            assert!(matches!(&elems[1], Value::String(s) if s == "empty"));
+            assert!(matches!(&elems[2], Value::String(s) if s == "zero"));
+            assert!(matches!(&elems[3], Value::String(s) if s == "minus one"));
+            assert!(matches!(&elems[4], Value::String(s) if s == "greeting"));
+            match &elems[5] {
+                Value::Tuple(fields) => {
+                    assert!(matches!(&fields[0], Value::String(s) if s == "ann"));
+                    assert!(matches!(fields[1], Value::Int(30)));
+                }
+                v => panic!("expected tuple, got {}", v),
+            }
+            assert!(matches!(&elems[6], Value::String(s) if s == "other"));
+            assert!(matches!(&elems[7], Value::String(s) if s == "NoMatchingArm"));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_defer() {
+    let val = exec_source("../../test_sources/defer.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(9)));
+            // LIFO on return
+            assert!(matches!(elems[1], Value::Int(21)));
+            assert!(matches!(elems[2], Value::Int(321)));
+            // runs on runtime errors
+            assert!(matches!(elems[3], Value::Int(4)));
+            assert!(matches!(&elems[4], Value::String(s) if s == "DivisionByZero"));
+            // block scopes and loop iterations
+            assert!(matches!(elems[5], Value::Int(657)));
+            assert!(matches!(elems[6], Value::Int(8182)));
+            // deferred statements continued by `else` and `catch`
+            assert!(matches!(elems[7], Value::Int(431)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_strings() {
+    let val = exec_source("../../test_sources/strings.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(11)));
+            assert!(matches!(&elems[1], Value::String(s) if s == "é"));
+            assert!(matches!(&elems[2], Value::String(s) if s == "örld"));
+            assert!(matches!(&elems[3], Value::String(s) if s == "héllo"));
+            assert!(matches!(&elems[4], Value::String(s) if s == "wörld"));
+            assert!(matches!(&elems[5], Value::String(s) if s == "hi, there"));
+            assert!(matches!(elems[6], Value::Int(15)));
+            // leading blanks and `#` inside strings are kept
+            assert!(matches!(elems[7], Value::Int(17)));
+            assert!(matches!(&elems[8], Value::List(l)
+                if matches!(l[..], [Value::Int(2), Value::Int(3)])));
+            assert!(matches!(elems[9], Value::Int(20)));
+            assert!(matches!(elems[10], Value::Bool(true)));
+            assert!(matches!(&elems[11], Value::String(s) if s == "IndexOutOfBounds"));
+            assert!(matches!(elems[12], Value::Int(3)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_format() {
+    let val = exec_source("../../test_sources/format.sl", false).unwrap();
+    let expected = [
+        "x = 2, p = 30",
+        "ann is 31 next year",
+        "{literal}",
+        "[a|   42|b   |**mid**]",
+        "3.14    2.500",
+        "tru",
+        "FormatArgNumMismatch",
+        "{x}",
+        "{2} {x}",
+        "{x} {{x}}",
+        "unbalanced { x",
+        "set: 2 }",
+    ];
+    match val {
+        Value::Tuple(elems) => {
+            assert_eq!(elems.len(), expected.len());
+            for (elem, expected) in elems.iter().zip(expected) {
+                assert!(
+                    matches!(elem, Value::String(s) if s == expected),
+                    "{}",
+                    elem
+                );
+            }
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_display() {
+    let val = exec_source("../../test_sources/display.sl", false).unwrap();
+    let expected = [
+        "3",
+        r#"("hi", excel, nil, [1, 2], (true,))"#,
+        r#"Person { name: "bob", age: 2.0, friend: Person { name: "ann", age: 30, friend: <cycle Person#"#,
+        r#"Person { name: "ann", age: 30, friend: Person { name: "bob", age: 2.0, friend: <cycle Person#"#,
+        "true",
+    ];
+    match val {
+        Value::Tuple(elems) => {
+            assert_eq!(elems.len(), expected.len());
+            for (elem, expected) in elems.iter().zip(expected) {
+                assert!(
+                    matches!(elem, Value::String(s) if s.starts_with(expected)),
+                    "{}",
+                    elem
+                );
+            }
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_display_depth() {
+    let tokens = sl_parse_file("../../test_sources/display.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.8, false).unwrap();
+    rt.display_depth = 1;
+    let val = match rt.run() {
+        Err(TwiError::Return(val)) => val,
+        _ => panic!("expected return"),
+    };
+    let elems = rt.heap.elements(val).unwrap();
+    assert_eq!(
+        rt.display(elems[1]),
+        r#""(\"hi\", excel, nil, [..], (..))""#
+    );
+    assert_eq!(
+        rt.display(elems[2]),
+        r#""Person { name: \"bob\", age: 2.0, friend: Person { .. } }""#
+    );
+}
+
+#[test]
+fn test_numeric() {
+    let val = exec_source("../../test_sources/numeric.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Float(x) if x == 1.5));
+            assert!(matches!(elems[1], Value::Float(x) if x == 6.0));
+            assert!(matches!(elems[2], Value::Int(3)));
+            assert!(matches!(elems[3], Value::Float(x) if x == 3.5));
+            assert!(matches!(elems[4], Value::Float(x) if x == 1.5));
+            assert!(matches!(elems[5], Value::Int(1024)));
+            assert!(matches!(elems[6], Value::Int(-4)));
+            assert!(matches!(elems[7], Value::Int(512)));
+            assert!(matches!(elems[8], Value::Float(x) if x == 0.5));
+            assert!(matches!(elems[9], Value::Bool(true)));
+            assert!(matches!(elems[10], Value::Bool(true)));
+            assert!(matches!(elems[11], Value::Int(3)));
+            assert!(matches!(elems[12], Value::Int(-3)));
+            assert!(matches!(elems[13], Value::Int(42)));
+            assert!(matches!(elems[14], Value::Float(x) if x == 2.0));
+            assert!(matches!(elems[15], Value::Float(x) if x == 2.5));
+            // promoted to a big integer instead of overflowing
+            assert!(matches!(elems[16], Value::Nil));
+            assert!(matches!(&elems[17], Value::String(s) if s == "Overflow"));
+            assert!(matches!(&elems[18], Value::String(s) if s == "InvalidConversion"));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_bigint() {
+    let val = exec_source("../../test_sources/bigint.sl", false).unwrap();
+    let big = |s: &str| s.parse::<BigInt>().unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(
+                matches!(&elems[0], Value::BigInt(x) if *x == big("265252859812191058636308480000000"))
+            );
+            assert!(matches!(elems[1], Value::Bool(true)));
+            assert!(matches!(&elems[2], Value::BigInt(x) if *x == big("354224848179261915075")));
+            assert!(
+                matches!(&elems[3], Value::BigInt(x) if *x == big("1267650600228229401496703205376"))
+            );
+            // results that fit are demoted back to ints
+            assert!(matches!(elems[4], Value::Int(i64::MAX)));
+            assert!(matches!(&elems[5], Value::BigInt(x) if *x == big("9223372036854775808")));
+            assert!(matches!(elems[6], Value::Int(870)));
+            assert!(matches!(elems[7], Value::Bool(true)));
+            assert!(matches!(elems[8], Value::Bool(true)));
+            assert!(matches!(elems[9], Value::Bool(true)));
+            assert!(
+                matches!(&elems[10], Value::String(s) if s == "f30 = 265252859812191058636308480000000")
+            );
+            assert!(matches!(elems[11], Value::Float(x) if x == 18446744073709551616.0));
+            // results over MAX_INT_BITS raise rather than hang
+            assert!(matches!(&elems[12], Value::String(s) if s == "Overflow"));
+            assert!(matches!(elems[13], Value::Nil));
+            assert!(matches!(&elems[14], Value::String(s) if s == "Overflow"));
+            assert!(matches!(elems[15], Value::Int(0)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_bitwise() {
+    let val = exec_source("../../test_sources/bitwise.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(8)));
+            assert!(matches!(elems[1], Value::Int(14)));
+            assert!(matches!(elems[2], Value::Int(6)));
+            assert!(matches!(elems[3], Value::Int(-6)));
+            assert!(matches!(elems[4], Value::Int(1024)));
+            assert!(matches!(elems[5], Value::Int(-4)));
+            assert!(matches!(elems[6], Value::Int(5)));
+            assert!(matches!(elems[7], Value::Bool(true)));
+            // | below ^ below &: 1 | (2 ^ (3 & 4))
+            assert!(matches!(elems[8], Value::Int(3)));
+            assert!(matches!(&elems[9], Value::BigInt(x) if *x == BigInt::from(1u128 << 64)));
+            assert!(matches!(elems[10], Value::Int(2)));
+            assert!(matches!(elems[11], Value::Int(255)));
+            assert!(matches!(&elems[12], Value::String(s) if s == "NegativeShift"));
+            assert!(matches!(elems[13], Value::Int(0)));
+            assert!(matches!(&elems[14], Value::String(s) if s == "Overflow"));
+            assert!(matches!(elems[15], Value::Nil));
+            assert!(matches!(&elems[16], Value::String(s) if s == "Overflow"));
+            assert!(matches!(elems[17], Value::Int(0)));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_stdlib() {
+    let val = exec_source("../../test_sources/stdlib.sl", false).unwrap();
+    let Value::Tuple(elems) = val else {
+        panic!("expected tuple, got {}", val);
+    };
+    let texts = |val: &Value| match val {
+        Value::List(elems) => elems
+            .iter()
+            .map(|elem| elem.to_string())
+            .collect::<Vec<_>>(),
+        v => panic!("expected list, got {}", v),
+    };
+    assert!(matches!(elems[0], Value::Int(5)));
+    assert!(matches!(elems[1], Value::Int(3)));
+    assert!(matches!(&elems[2], Value::String(s) if s == "1.5[1, \"a\"]"));
+    assert!(matches!(elems[3], Value::Int(3)));
+    assert!(matches!(elems[4], Value::Float(x) if x == 2.5));
+    assert!(matches!(elems[5], Value::Int(1)));
+    assert!(matches!(elems[6], Value::Int(9)));
+    assert!(matches!(&elems[7], Value::String(s) if s == "pear"));
+    assert!(matches!(elems[8], Value::Float(x) if x == 4.0));
+    assert!(matches!(elems[9], Value::Int(1024)));
+    assert!(matches!(elems[10], Value::Int(-3)));
+    assert!(matches!(elems[11], Value::Int(7)));
+    assert_eq!(texts(&elems[12]), ["\"a\"", "\" b \"", "\"c\""]);
+    assert!(matches!(&elems[13], Value::String(s) if s == "a| b |c"));
+    assert!(matches!(&elems[14], Value::String(s) if s == "b"));
+    assert!(matches!(elems[15], Value::Int(2)));
+    assert!(matches!(elems[16], Value::Int(-1)));
+    assert!(matches!(&elems[17], Value::String(s) if s == "a+b+c"));
+    assert!(matches!(&elems[18], Value::String(s) if s == "ABC"));
+    assert!(matches!(&elems[19], Value::String(s) if s == "abc"));
+    assert!(matches!(&elems[20], Value::String(s) if s == "   7"));
+    assert!(matches!(elems[21], Value::Float(x) if x == 12.5));
+    assert!(matches!(&elems[22], Value::Native { native, .. } if native.name == "len"));
+}
+
+#[test]
+fn test_stdlib_override() {
+    let val = exec_source("../../test_sources/stdlib_override.sl", false).unwrap();
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(42)));
+            assert!(matches!(&elems[1], Value::String(s) if s == "shadowed"));
+            assert!(matches!(&elems[2], Value::String(s) if s == "abc"));
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+}
+
+#[test]
+fn test_register_native() {
+    let source = r#"
+        let (sum, pair) = (add3(1, 2, 3), swap(1, "a"));
+        let err = nil;
+        try {
+            add3(1, 2);
+        } catch e {
+            err = e.kind;
+        }
+        return (sum, pair, err, called);
+    "#;
+    let tokens = lex::pest_parse::sl_parse_str(source).unwrap();
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.8, true).unwrap();
+
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counter = calls.clone();
+    rt.register_native("add3", 3, move |rt, args| {
+        counter.set(counter.get() + 1);
+        let mut sum = 0;
+        for &arg in args {
+            match rt.value(arg) {
+                Value::Int(x) => sum += x,
+                v => {
+                    return Err(TwiError::UnexpectedType {
+                        expected: "Int".into(),
+                        got: v.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(rt.alloc_value(Value::Int(sum)))
+    });
+    rt.register_native("swap", 2, |rt, args| {
+        let pair = Value::Tuple(vec![rt.value(args[1]), rt.value(args[0])]);
+        Ok(rt.alloc_value(pair))
+    });
+    // a native bound as a global value, like any other
+    rt.register_native("called", 0, |rt, _| Ok(rt.alloc_value(Value::Nil)));
+
+    let val = match rt.run() {
+        Err(TwiError::Return(val)) => rt.value(val),
+        res => panic!("expected return, got {:?}", res.map(|_| ())),
+    };
+    match val {
+        Value::Tuple(elems) => {
+            assert!(matches!(elems[0], Value::Int(6)));
+            assert_eq!(elems[1].to_string(), "(\"a\", 1)");
+            assert!(matches!(&elems[2], Value::String(s) if s == "ArgNumMismatch"));
+            assert_eq!(elems[3].to_string(), "<native called>");
+        }
+        v => panic!("expected tuple, got {}", v),
+    }
+    // the failed call is rejected before the function runs
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn test_engine() {
+    let mut engine = Engine::new();
+    engine.load_path("../../test_sources/engine.sl").unwrap();
+
+    let sum: i64 = engine.call("add", vec![1.to_sl(), 2.to_sl()]).unwrap();
+    assert_eq!(sum, 3);
+    let joined: String = engine.call("add", vec!["a".to_sl(), "b".to_sl()]).unwrap();
+    assert_eq!(joined, "ab");
+    let big: BigInt = engine.call("factorial", vec![25.to_sl()]).unwrap();
+    assert_eq!(big.to_string(), "15511210043330985984000000");
+    let p: Value = engine.call("point", vec![1.to_sl(), 2.5.to_sl()]).unwrap();
+    assert_eq!(p.to_string(), "Point { x: 1, y: 2.5 }");
+
+    // globals persist between calls and loads
+    engine.call::<i64>("tick", vec![]).unwrap();
+    assert_eq!(engine.call::<i64>("tick", vec![]).unwrap(), 2);
+    assert_eq!(engine.get_global::<i64>("counter").unwrap(), 2);
+    engine.set_global("counter", 10).unwrap();
+    assert_eq!(engine.call::<i64>("tick", vec![]).unwrap(), 11);
+    engine.set_global("pair", (1, vec!["x"])).unwrap();
+    engine.load_str("let snd = pair[1][0];").unwrap();
+    assert_eq!(engine.get_global::<String>("snd").unwrap(), "x");
+    engine
+        .load_str("func pair_of() { return (7, nil); }")
+        .unwrap();
+    let (a, b): (i64, Option<bool>) = engine.call("pair_of", vec![]).unwrap();
+    assert_eq!((a, b), (7, None));
+
+    // errors are returned
+    assert!(matches!(
+        engine.set_global("origin", 1),
+        Err(TwiError::AssignToConst(_))
+    ));
+    assert!(matches!(
+        engine.call::<i64>("add", vec![1.to_sl()]),
+        Err(TwiError::MissingArg { .. })
+    ));
+    assert!(matches!(
+        engine.call::<String>("add", vec![1.to_sl(), 2.to_sl()]),
+        Err(TwiError::UnexpectedType { .. })
+    ));
+    assert!(matches!(
+        engine.call::<()>("nope", vec![]),
+        Err(TwiError::IdentifierNotFound(_))
+    ));
+    match engine.call::<()>("fail", vec!["boom".to_sl()]) {
+        Err(TwiError::Throw(obj)) => assert_eq!(engine.value(obj).to_string(), "\"boom\""),
+        res => panic!("expected throw, got {:?}", res),
+    }
+    assert!(matches!(
+        engine.load_str("let = ;"),
+        Err(TwiError::Syntax(_))
+    ));
+    assert!(matches!(
+        engine.load_path("missing.sl"),
+        Err(TwiError::Io(_))
+    ));
+}
+
+#[test]
+fn test_engine_persistent() {
+    let mut engine = Engine::new();
+    engine.load_path("../../test_sources/engine.sl").unwrap();
+    engine.load_str("func x_of(p) { return p.x; }").unwrap();
+
+    let p = engine
+        .call_persistent("point", vec![1.to_sl(), 2.to_sl()])
+        .unwrap();
+    for i in 0..3 {
+        engine
+            .call::<Value>("point", vec![i.to_sl(), i.to_sl()])
+            .unwrap();
+        engine.collect_garbage();
+    }
+
+    // the held model and its fields survive, and can be passed back to SL
+    assert_eq!(engine.value(p.object()).to_string(), "Point { x: 1, y: 2 }");
+    let held = engine.value(p.object());
+    assert_eq!(engine.call::<i64>("x_of", vec![held]).unwrap(), 1);
+}
+
+#[test]
+fn test_output() {
+    // prefixed by default
+    let tokens = sl_parse_file("../../test_sources/output.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.8, true).unwrap();
+    let out = CapturedOutput::new();
+    rt.set_output(out.clone());
+    rt.run().unwrap();
+    assert_eq!(
+        out.contents(),
+        "[SL print] hello 3\n[SL print] sl!\n[SL print] [1, 2] nil\n"
+    );
+
+    // engines print plain
+    let mut engine = Engine::new();
+    let out = CapturedOutput::new();
+    engine.set_output(out.clone());
+    engine.load_path("../../test_sources/output.sl").unwrap();
+    assert_eq!(out.contents(), "hello 3\nsl!\n[1, 2] nil\n");
+
+    // global initializers run once the output is configured
+    let tokens = sl_parse_file("../../test_sources/global_print.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.8, false).unwrap();
+    let out = CapturedOutput::new();
+    rt.set_output(out.clone());
+    rt.set_plain(true);
+    assert!(matches!(rt.run(), Err(TwiError::Return(_))));
+    assert_eq!(out.contents(), "init\n");
+}
+
+#[test]
+fn test_io_capabilities() {
+    let mut engine = Engine::new();
+    engine.load_path("../../test_sources/io.sl").unwrap();
+
+    // everything is denied by default
+    let kind: String = engine.call("read_or_kind", vec!["io.sl".to_sl()]).unwrap();
+    assert_eq!(kind, "PermissionDenied");
+    let kind: String = engine.call("line_or_kind", vec![]).unwrap();
+    assert_eq!(kind, "PermissionDenied");
+
+    let dir = std::env::temp_dir().join(format!("sl_io_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    engine.set_capabilities(Capabilities {
+        fs_root: Some(dir.clone()),
+        stdin: true,
+    });
+    engine.set_input(std::io::Cursor::new("first\r\nsecond"));
+
+    let text: String = engine
+        .call("save", vec!["out.txt".to_sl(), "hi".to_sl()])
+        .unwrap();
+    assert_eq!(text, "hi!");
+    assert_eq!(std::fs::read_to_string(dir.join("out.txt")).unwrap(), "hi!");
+    engine
+        .load_str("let found = (exists(\"out.txt\"), exists(\"none.txt\"));")
+        .unwrap();
+    let found: (bool, bool) = engine.get_global("found").unwrap();
+    assert_eq!(found, (true, false));
+
+    // paths may not leave the root
+    let kind: String = engine.call("read_or_kind", vec!["../x".to_sl()]).unwrap();
+    assert_eq!(kind, "PermissionDenied");
+    let kind: String = engine
+        .call("read_or_kind", vec!["/etc/hostname".to_sl()])
+        .unwrap();
+    assert_eq!(kind, "PermissionDenied");
+
+    // nor follow links out of it, dangling or not
+    let outside = std::env::temp_dir().join(format!("sl_io_outside_{}", std::process::id()));
+    std::fs::create_dir_all(&outside).unwrap();
+    std::os::unix::fs::symlink(outside.join("escaped.txt"), dir.join("link")).unwrap();
+    std::os::unix::fs::symlink(&outside, dir.join("dirlink")).unwrap();
+    for path in ["link", "dirlink/escaped.txt", "./link"] {
+        let kind: Option<String> = engine
+            .call("write_or_kind", vec![path.to_sl(), "pwned".to_sl()])
+            .unwrap();
+        assert_eq!(kind.as_deref(), Some("PermissionDenied"), "{}", path);
+    }
+    assert!(!outside.join("escaped.txt").exists());
+    std::fs::remove_dir_all(&outside).unwrap();
+    let kind: String = engine
+        .call("read_or_kind", vec!["none.txt".to_sl()])
+        .unwrap();
+    assert_eq!(kind, "Io");
+
+    let lines: Vec<Option<String>> = (0..3)
+        .map(|_| engine.call("line_or_kind", vec![]).unwrap())
+        .collect();
+    assert_eq!(lines, [Some("first".into()), Some("second".into()), None]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_exit_code() {
+    let run = |path: &str, args: &[&str]| {
+        let mut cfg = InterpreterConfig::default(path);
+        cfg.plain = true;
+        cfg.args = args.iter().map(|arg| arg.to_string()).collect();
+        run_program(cfg)
+    };
+
+    assert_eq!(run("../../test_sources/exit.sl", &["40", "-x"]), 42);
+    assert_eq!(run("../../test_sources/exit.sl", &[]), EXIT_RUNTIME_ERROR);
+    assert_eq!(run("../../test_sources/exit.sl", &["throw"]), EXIT_UNCAUGHT);
+    // exit codes are 0 to 255, other ints are errors rather than truncated
+    assert_eq!(run("../../test_sources/exit.sl", &["254"]), 255);
+    assert_eq!(
+        run("../../test_sources/exit.sl", &["255"]),
+        EXIT_RUNTIME_ERROR
+    );
+    assert_eq!(
+        run("../../test_sources/exit.sl", &["-2"]),
+        EXIT_RUNTIME_ERROR
+    );
+    assert_eq!(
+        run("../../test_sources/exit.sl", &["big"]),
+        EXIT_RUNTIME_ERROR
+    );
+    // `interpreter exit.sl 40 -x` runs exit.sl, rather than a REPL
+    let mut cfg = InterpreterConfig::default("unused.sl");
+    cfg.path = None;
+    cfg.plain = true;
+    cfg.args = ["../../test_sources/exit.sl", "40", "-x"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    assert_eq!(run_program(cfg), 42);
+    assert_eq!(run("../../test_sources/script.sl", &[]), 6);
+    assert_eq!(run("../../test_sources/tuple.sl", &[]), 0);
+    // failing global initializers are runtime errors, not compile errors
+    assert_eq!(
+        run("../../test_sources/global_error.sl", &[]),
+        EXIT_RUNTIME_ERROR
+    );
+    assert_eq!(
+        run("../../test_sources/const_reassign.sl", &[]),
+        EXIT_COMPILE_ERROR
+    );
+    assert_eq!(
+        run("../../test_sources/syntax_error.sl", &[]),
+        EXIT_COMPILE_ERROR
+    );
+    assert_eq!(run("../../test_sources/none.sl", &[]), EXIT_COMPILE_ERROR);
+}
+
+#[test]
+fn test_repl() {
+    let mut repl = Repl::new(&InterpreterConfig::default("unused.sl"));
+    let out = CapturedOutput::new();
+    repl.rt.set_output(out.clone());
+    let mut eval = |input: &str| match repl.eval(input) {
+        Ok(text) => text,
+        Err(e) => Some(repl.describe(&e)),
+    };
+
+    assert!(Repl::is_incomplete("func f() {\n  if x {"));
+    assert!(Repl::is_incomplete("print \"{\n"));
+    assert!(!Repl::is_incomplete("func f() { return \"}\"; }"));
+    // escaped quotes do not end strings, brackets in comments do not count
+    assert!(!Repl::is_incomplete("print \"a\\\"{\";"));
+    assert!(Repl::is_incomplete("print \"a\\\";"));
+    assert!(!Repl::is_incomplete("let x = 1; <// { //>"));
+    assert!(!Repl::is_incomplete("  # (\"\nlet x = #[1];"));
+    assert!(Repl::is_incomplete("let x = 1; <// {"));
+
+    // definitions and bindings persist between inputs
+    assert_eq!(eval("let x = 20;"), None);
+    assert_eq!(eval("func double(n) {\n    return n * 2;\n}"), None);
+    assert_eq!(eval("model P {\n    a,\n}"), None);
+    assert_eq!(eval("double(x) + 2"), Some("42".to_string()));
+    assert_eq!(
+        eval("x = x + 1; str(x) + \"s\""),
+        Some("\"21s\"".to_string())
+    );
+    assert_eq!(eval("nil"), None);
+    assert_eq!(eval("print double(1);"), None);
+    assert_eq!(out.contents(), "2\n");
+
+    // errors leave the session usable
+    assert_eq!(eval("y"), Some("Identifier not found: y".to_string()));
+    assert_eq!(
+        eval("throw \"oops\";"),
+        Some("uncaught exception: \"oops\"".to_string())
+    );
+    assert_eq!(eval("x"), Some("21".to_string()));
+
+    assert_eq!(
+        eval(":tokens x;"),
+        Some("[Identifier(\"x\"), Semicolon, Eof]".to_string())
+    );
+    assert!(eval(":ast 1 + 2").unwrap().contains("Expression"));
+    assert!(eval(":heap").unwrap().contains("<native len>"));
+    assert!(eval(":nope").unwrap().contains("unknown command"));
+}
+
+#[test]
+fn test_backtrace() {
+    let tokens = sl_parse_file("../../test_sources/backtrace.sl");
+    let root = Parser::new_from_iter(tokens).parse_stmt().unwrap();
+    let mut rt = Runtime::try_new(root, 0.8, false).unwrap();
+    rt.set_output(CapturedOutput::new());
+
+    // the error caught in main leaves no trace, the uncaught one does
+    let err = rt.run().unwrap_err();
+    assert!(matches!(err, TwiError::IdentifierNotFound(_)));
+    assert_eq!(
+        rt.backtrace_lines(),
+        ["in inner(x)", "called from middle(2)", "called from main"]
+    );
+
+    // natives get frames too, and host calls start from the script frame
+    let mut repl = Repl::new(&InterpreterConfig::default("unused.sl"));
+    repl.eval("func parse(s) { return int(s); }").unwrap();
+    let err = repl.eval("parse(\"x\")").unwrap_err();
+    assert_eq!(
+        repl.describe(&err),
+        "Cannot convert \"x\" to int\n    in int(s)\n    called from parse(\"x\")\n    called from <script>"
+    );
+    assert_eq!(repl.eval("parse(\"1\")").unwrap(), Some("1".to_string()));
+    assert!(repl.rt.backtrace_lines().is_empty());
+}
+
+#[test]
+fn test_stack_overflow() {
+    // deep recursion runs off the test thread's stack, unbounded recursion is caught
+    let val = exec_source("../../test_sources/recursion.sl", false).unwrap();
+    assert_eq!(val.to_string(), "(\"StackOverflow\", 9000)");
+
+    let mut cfg = InterpreterConfig::default("unused.sl");
+    cfg.max_call_depth = 3;
+    let mut repl = Repl::new(&cfg);
+    repl.eval("func count(n) { if n == 0 { return 0; } return 1 + count(n - 1); }")
+        .unwrap();
+    assert_eq!(repl.eval("count(2)").unwrap(), Some("2".to_string()));
+    let err = repl.eval("count(3)").unwrap_err();
+    assert!(matches!(&err, TwiError::StackOverflow { funcname, depth: 3 } if funcname == "count"));
+    assert_eq!(
+        repl.describe(&err),
+        "Stack overflow: more than 3 nested calls, in function count\n    in count(n - 1)\n    called from count 2 more times\n    called from <script>"
+    );
+}